@@ -0,0 +1,141 @@
+//! Compares path lookup in [MapSortedArchived] (used by `pack`) against
+//! archived [HashMap] (used before).
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use rkyv::{Archive, Serialize, rancor::Error, util::AlignedVec};
+use std::{collections::HashMap, hint::black_box};
+use web_static_pack_common::map_sorted::{MapSorted, MapSortedArchived};
+
+#[derive(Archive, Serialize)]
+struct ContainerHashMap {
+    map: HashMap<String, u32>,
+}
+
+#[derive(Archive, Serialize)]
+struct ContainerMapSorted {
+    #[rkyv(with = MapSorted)]
+    map: HashMap<String, u32>,
+}
+
+// pack-like paths, eg. `/assets/12/34/file-1234.js`
+fn paths(count: usize) -> Vec<String> {
+    (0..count)
+        .map(|index| {
+            format!(
+                "/assets/{}/{}/file-{}.js",
+                index % 100,
+                index / 100 % 100,
+                index
+            )
+        })
+        .collect()
+}
+
+fn lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lookup");
+
+    for count in [100, 10_000, 100_000] {
+        let paths = paths(count);
+        let map = paths
+            .iter()
+            .enumerate()
+            .map(|(index, path)| (path.clone(), index as u32))
+            .collect::<HashMap<_, _>>();
+
+        let serialized_hash_map: AlignedVec =
+            rkyv::to_bytes::<Error>(&ContainerHashMap { map: map.clone() }).unwrap();
+        let hash_map = &rkyv::access::<ArchivedContainerHashMap, Error>(&serialized_hash_map)
+            .unwrap()
+            .map;
+
+        let serialized_map_sorted: AlignedVec =
+            rkyv::to_bytes::<Error>(&ContainerMapSorted { map }).unwrap();
+        let map_sorted: &MapSortedArchived<_, _> =
+            &rkyv::access::<ArchivedContainerMapSorted, Error>(&serialized_map_sorted)
+                .unwrap()
+                .map;
+
+        // every 7th path, so lookups are spread across the map
+        let paths_hit = paths
+            .iter()
+            .step_by(7)
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        let paths_miss = paths_hit
+            .iter()
+            .map(|path| format!("{path}.map"))
+            .collect::<Vec<_>>();
+
+        group.bench_function(BenchmarkId::new("hash_map_hit", count), |b| {
+            b.iter(|| {
+                for path in &paths_hit {
+                    black_box(hash_map.get(black_box(*path)));
+                }
+            })
+        });
+        group.bench_function(BenchmarkId::new("map_sorted_hit", count), |b| {
+            b.iter(|| {
+                for path in &paths_hit {
+                    black_box(map_sorted.get(black_box(*path)));
+                }
+            })
+        });
+        group.bench_function(BenchmarkId::new("hash_map_miss", count), |b| {
+            b.iter(|| {
+                for path in &paths_miss {
+                    black_box(hash_map.get(black_box(path.as_str())));
+                }
+            })
+        });
+        group.bench_function(BenchmarkId::new("map_sorted_miss", count), |b| {
+            b.iter(|| {
+                for path in &paths_miss {
+                    black_box(map_sorted.get(black_box(path.as_str())));
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn prefix(c: &mut Criterion) {
+    let mut group = c.benchmark_group("prefix");
+
+    let paths = paths(100_000);
+    let map = paths
+        .iter()
+        .enumerate()
+        .map(|(index, path)| (path.clone(), index as u32))
+        .collect::<HashMap<_, _>>();
+
+    let serialized_hash_map: AlignedVec =
+        rkyv::to_bytes::<Error>(&ContainerHashMap { map: map.clone() }).unwrap();
+    let hash_map = &rkyv::access::<ArchivedContainerHashMap, Error>(&serialized_hash_map)
+        .unwrap()
+        .map;
+
+    let serialized_map_sorted: AlignedVec =
+        rkyv::to_bytes::<Error>(&ContainerMapSorted { map }).unwrap();
+    let map_sorted = &rkyv::access::<ArchivedContainerMapSorted, Error>(&serialized_map_sorted)
+        .unwrap()
+        .map;
+
+    // hash map can only be scanned entirely
+    group.bench_function("hash_map_scan", |b| {
+        b.iter(|| {
+            hash_map
+                .iter()
+                .filter(|(path, _)| path.starts_with(black_box("/assets/42/")))
+                .count()
+        })
+    });
+    group.bench_function("map_sorted_iter_prefix", |b| {
+        b.iter(|| map_sorted.iter_prefix(black_box("/assets/42/")).count())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, lookup, prefix);
+criterion_main!(benches);
@@ -1,10 +1,10 @@
 //! Cache control types used by file.
 
-use rkyv::{Archive, Serialize};
+use rkyv::{Archive, Deserialize, Serialize};
 
 /// Type representing cache control of a file. This will correspond to
 /// `cache-control` header set in http response.
-#[derive(Archive, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Archive, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
 #[rkyv(archived = CacheControlArchived)]
 #[rkyv(derive(Clone, Copy, PartialEq, Eq, Debug))]
 pub enum CacheControl {
@@ -1,7 +1,8 @@
 //! File represents single item of a Pack, accessible under specific path.
 
 use crate::cache_control::CacheControl;
-use rkyv::{Archive, Serialize};
+use rkyv::{Archive, Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
 
 /// [File] represents an original file from filesystem with all fields
 /// precalculated. It contains `gzip` / `brotli` compressed content,
@@ -10,7 +11,7 @@ use rkyv::{Archive, Serialize};
 /// [File] is created in packing phase (once) to allow fast loading in loader
 /// without need to perform expensive computations (like calculating compressed
 /// forms) in runtime.
-#[derive(Archive, Serialize, Debug)]
+#[derive(Archive, Serialize, Deserialize, Debug)]
 #[rkyv(archived = FileArchived)]
 #[rkyv(derive(Debug))]
 #[rkyv(attr(allow(missing_docs)))] // TODO: resolve with https://github.com/rkyv/rkyv/issues/561
@@ -30,3 +31,34 @@ pub struct File {
     /// `cache-control` options for the file.
     pub cache_control: CacheControl,
 }
+
+/// Calculates `ETag` header from file contents.
+///
+/// Used by packer when building [File] and by loader when verifying it.
+pub fn etag_from_content(content: &[u8]) -> String {
+    let mut etag = Sha3_256::new();
+    etag.update(content);
+    let etag = etag.finalize();
+    let etag = format!("\"{:x}\"", &etag); // `ETag` as "quoted" hex sha3. Quote is required by standard
+    etag
+}
+
+#[cfg(test)]
+mod test {
+    use super::etag_from_content;
+
+    #[test]
+    fn etag_from_content_returns_expected() {
+        // two identical payloads should produce identical `ETag`
+        // two different payloads should produce different `ETag`
+
+        assert_eq!(
+            etag_from_content(b"lorem ipsum"),
+            etag_from_content(b"lorem ipsum")
+        );
+        assert_ne!(
+            etag_from_content(b"lorem ipsum"),
+            etag_from_content(b"ipsum lorem")
+        );
+    }
+}
@@ -5,14 +5,16 @@
 //! For a project documentation, examples, etc. see
 //! [web-static-pack](https://github.com/peku33/web-static-pack).
 //!
-//! The root type of this crate is [pack::Pack]. It's a collection (a hashmap)
+//! The root type of this crate is [pack::Pack]. It's a collection (a map)
 //! of files [file::File] distinguished by [pack_path::PackPath] (a custom type
 //! for path including some sanity checks).
 //!
 //! web-static-pack uses [rkyv] for serialization. Each module provides a rust
 //! native type, used during `pack` building, ex. [pack::Pack] and [rkyv]
 //! macro-generated zero-copy loadable (aka. mmapable) representation, eg.
-//! [pack::PackArchived], used by loader.
+//! [pack::PackArchived], used by loader. Archived representation can be also
+//! deserialized back into rust native type, ex. when packer is merging
+//! existing `pack`s.
 //!
 //! ### Note
 //!
@@ -24,6 +26,7 @@
 
 pub mod cache_control;
 pub mod file;
+pub mod map_sorted;
 pub mod pack;
 pub mod pack_path;
 
@@ -31,4 +34,30 @@ pub mod pack_path;
 pub const PACK_FILE_MAGIC: u64 = 0x479a01809f24813c;
 /// File version, used by loader to detect if loader and packer versions are
 /// compatible.
-pub const PACK_FILE_VERSION: u64 = 2;
+pub const PACK_FILE_VERSION: u64 = 4;
+
+/// Length of the header preceding serialized [pack::Pack].
+///
+/// Header consists of (all numbers are native endian `u64`):
+/// - [PACK_FILE_MAGIC],
+/// - [PACK_FILE_VERSION],
+/// - checksum algorithm, one of `PACK_FILE_CHECKSUM_ALGORITHM_*`,
+/// - payload (serialized [pack::Pack]) length,
+/// - 32 bytes of payload checksum, zeroed if no checksum is used.
+///
+/// Header length is a multiple of 16, so payload alignment is preserved.
+pub const PACK_FILE_HEADER_LENGTH: usize = 64;
+/// No checksum is stored in the header.
+pub const PACK_FILE_CHECKSUM_ALGORITHM_NONE: u64 = 0;
+/// BLAKE3 (32 bytes) checksum of the payload is stored in the header.
+pub const PACK_FILE_CHECKSUM_ALGORITHM_BLAKE3: u64 = 1;
+
+/// Magic of the optional signature trailer, following the payload.
+pub const PACK_FILE_SIGNATURE_MAGIC: u64 = 0x8e0c3b5f1d27a6e4;
+/// Length of the optional signature trailer, following the payload.
+///
+/// Trailer consists of:
+/// - [PACK_FILE_SIGNATURE_MAGIC] (native endian `u64`),
+/// - 32 bytes of Ed25519 public key of the signer,
+/// - 64 bytes of Ed25519 signature of BLAKE3 hash of the header and payload.
+pub const PACK_FILE_SIGNATURE_TRAILER_LENGTH: usize = 104;
@@ -0,0 +1,462 @@
+//! Contains [MapSorted], [rkyv] wrapper serializing [HashMap] into a sorted
+//! array with a hash index, and its archived representation
+//! [MapSortedArchived].
+
+use rkyv::{
+    Archive, Deserialize, Place, Portable, Serialize,
+    bytecheck::{CheckBytes, Verify},
+    hash::{FxHasher64, hash_value},
+    munge::munge,
+    primitive::ArchivedU32,
+    rancor::{Fallible, Source, fail},
+    ser::{Allocator, Writer},
+    vec::{ArchivedVec, VecResolver},
+    with::{ArchiveWith, DeserializeWith, SerializeWith},
+};
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    error::Error,
+    fmt,
+    hash::{BuildHasher, Hash},
+    ops::Index,
+};
+
+/// [rkyv] "with" wrapper for [HashMap], serializing entries as an array sorted
+/// by key, accompanied by a hash index built during serialization. Archived
+/// representation is [MapSortedArchived].
+///
+/// Sorted entries allow range (eg. prefix) queries done with binary search,
+/// while single key lookups go through the hash index. As entries are
+/// serialized in order and index uses fixed hash function, serialized output
+/// depends only on map content, not on [HashMap] (random) hasher state.
+///
+/// As with [HashMap], ordering and hashing of `K`, `K::Archived` and `Q` used
+/// for lookups must be consistent.
+#[derive(Debug)]
+pub struct MapSorted;
+
+/// Archived representation of [HashMap] serialized with [MapSorted].
+///
+/// Keys and values are stored in separate arrays, keys are sorted in
+/// ascending order. Keeping keys together makes binary search more cache
+/// friendly. Hash index is an open addressing (linear probing) table, with
+/// size being a power of two at least twice the number of entries. Each slot
+/// contains index of the entry plus one, or zero if empty.
+#[derive(Portable, CheckBytes)]
+#[bytecheck(crate = rkyv::bytecheck, verify)]
+#[repr(C)]
+pub struct MapSortedArchived<K, V> {
+    keys: ArchivedVec<K>,
+    values: ArchivedVec<V>,
+    index: ArchivedVec<ArchivedU32>,
+}
+impl<K, V> MapSortedArchived<K, V> {
+    /// Returns number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Iterates over keys, in ascending order.
+    pub fn keys(&self) -> impl ExactSizeIterator<Item = &K> {
+        self.keys.iter()
+    }
+
+    /// Iterates over values, in order of their keys.
+    pub fn values(&self) -> impl ExactSizeIterator<Item = &V> {
+        self.values.iter()
+    }
+
+    /// Iterates over entries, in ascending order of keys.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (&K, &V)> {
+        self.keys.iter().zip(self.values.iter())
+    }
+
+    /// Looks up `key` in hash index, returns position of the entry.
+    fn position<Q>(
+        &self,
+        key: &Q,
+    ) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.index.is_empty() {
+            return None;
+        }
+
+        let mut slot = index_slot(hash_value::<Q, FxHasher64>(key), self.index.len());
+        // probing is limited to table size, so it ends even for invalid index
+        for _ in 0..self.index.len() {
+            let position = self.index[slot].to_native() as usize;
+            if position == 0 {
+                return None;
+            }
+            let position = position - 1;
+
+            if self.keys.get(position)?.borrow() == key {
+                return Some(position);
+            }
+
+            slot = (slot + 1) & (self.index.len() - 1);
+        }
+        None
+    }
+
+    /// Returns entry for given `key`, or [None] if `key` is not present.
+    pub fn get_key_value<Q>(
+        &self,
+        key: &Q,
+    ) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let position = self.position(key)?;
+
+        Some((&self.keys[position], self.values.get(position)?))
+    }
+
+    /// Returns value for given `key`, or [None] if `key` is not present.
+    pub fn get<Q>(
+        &self,
+        key: &Q,
+    ) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (_key, value) = self.get_key_value(key)?;
+        Some(value)
+    }
+
+    /// Returns `true` if map contains given `key`.
+    pub fn contains_key<Q>(
+        &self,
+        key: &Q,
+    ) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_key_value(key).is_some()
+    }
+
+    /// Iterates over entries with keys starting with `prefix`, in ascending
+    /// order of keys. Matching entries are found with binary search, so this
+    /// does not visit entries outside of returned range.
+    pub fn iter_prefix<'s>(
+        &'s self,
+        prefix: &str,
+    ) -> impl ExactSizeIterator<Item = (&'s K, &'s V)>
+    where
+        K: Borrow<str>,
+    {
+        // keys starting with prefix are placed right after keys lower than prefix
+        let start = self.keys.partition_point(|key| key.borrow() < prefix);
+        let length = self.keys[start..].partition_point(|key| key.borrow().starts_with(prefix));
+        let end = start + length;
+
+        self.keys[start..end]
+            .iter()
+            .zip(self.values.get(start..end).unwrap_or(&[]).iter())
+    }
+}
+impl<K, V, Q> Index<&Q> for MapSortedArchived<K, V>
+where
+    K: Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+{
+    type Output = V;
+
+    fn index(
+        &self,
+        key: &Q,
+    ) -> &Self::Output {
+        self.get(key).expect("key not found")
+    }
+}
+impl<K, V> fmt::Debug for MapSortedArchived<K, V>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Error returned during [MapSortedArchived] validation.
+#[derive(Debug)]
+enum MapSortedArchivedInvalid {
+    LengthMismatch,
+    KeysUnsorted,
+    IndexInvalid,
+}
+impl fmt::Display for MapSortedArchivedInvalid {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            Self::LengthMismatch => write!(f, "keys and values lengths do not match"),
+            Self::KeysUnsorted => write!(f, "keys are not sorted or not unique"),
+            Self::IndexInvalid => write!(f, "hash index is invalid"),
+        }
+    }
+}
+impl Error for MapSortedArchivedInvalid {}
+
+// SAFETY: verify only inspects already checked fields
+unsafe impl<K, V, C> Verify<C> for MapSortedArchived<K, V>
+where
+    K: Ord,
+    C: Fallible + ?Sized,
+    C::Error: Source,
+{
+    fn verify(
+        &self,
+        _context: &mut C,
+    ) -> Result<(), C::Error> {
+        if self.keys.len() != self.values.len() {
+            fail!(MapSortedArchivedInvalid::LengthMismatch);
+        }
+        if !self.keys.is_sorted_by(|key_a, key_b| key_a < key_b) {
+            fail!(MapSortedArchivedInvalid::KeysUnsorted);
+        }
+        if self.index.len() != index_length(self.keys.len())
+            || self
+                .index
+                .iter()
+                .any(|position| position.to_native() as usize > self.keys.len())
+        {
+            fail!(MapSortedArchivedInvalid::IndexInvalid);
+        }
+        Ok(())
+    }
+}
+
+/// Resolver for [MapSortedArchived].
+pub struct MapSortedResolver {
+    keys: VecResolver,
+    values: VecResolver,
+    index: VecResolver,
+}
+
+/// Returns hash index length for `length` entries.
+fn index_length(length: usize) -> usize {
+    if length == 0 {
+        return 0;
+    }
+    (length * 2).next_power_of_two()
+}
+/// Returns initial hash index slot for `hash`. Upper bits are used, as they
+/// are better mixed by [FxHasher64].
+fn index_slot(
+    hash: u64,
+    index_length: usize,
+) -> usize {
+    (hash >> (u64::BITS - index_length.trailing_zeros())) as usize
+}
+/// Builds hash index for keys in given order.
+fn index_build<'k, K>(keys: impl ExactSizeIterator<Item = &'k K>) -> Vec<u32>
+where
+    K: Hash + 'k,
+{
+    let mut index = vec![0; index_length(keys.len())];
+    for (position, key) in keys.enumerate() {
+        let mut slot = index_slot(hash_value::<K, FxHasher64>(key), index.len());
+        while index[slot] != 0 {
+            slot = (slot + 1) & (index.len() - 1);
+        }
+        index[slot] = u32::try_from(position + 1).unwrap();
+    }
+    index
+}
+
+impl<K, V, H> ArchiveWith<HashMap<K, V, H>> for MapSorted
+where
+    K: Archive,
+    V: Archive,
+{
+    type Archived = MapSortedArchived<K::Archived, V::Archived>;
+    type Resolver = MapSortedResolver;
+
+    fn resolve_with(
+        field: &HashMap<K, V, H>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        munge!(let MapSortedArchived { keys, values, index } = out);
+        ArchivedVec::resolve_from_len(field.len(), resolver.keys, keys);
+        ArchivedVec::resolve_from_len(field.len(), resolver.values, values);
+        ArchivedVec::resolve_from_len(index_length(field.len()), resolver.index, index);
+    }
+}
+
+impl<K, V, H, S> SerializeWith<HashMap<K, V, H>, S> for MapSorted
+where
+    K: Serialize<S> + Hash + Ord,
+    V: Serialize<S>,
+    S: Fallible + Writer + Allocator + ?Sized,
+{
+    fn serialize_with(
+        field: &HashMap<K, V, H>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        let mut entries = field.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|(key, _)| *key);
+
+        let keys = ArchivedVec::<K::Archived>::serialize_from_iter::<K, _, _>(
+            entries.iter().map(|(key, _)| *key),
+            serializer,
+        )?;
+        let values = ArchivedVec::<V::Archived>::serialize_from_iter::<V, _, _>(
+            entries.iter().map(|(_, value)| *value),
+            serializer,
+        )?;
+        let index = ArchivedVec::<ArchivedU32>::serialize_from_slice::<u32, _>(
+            &index_build(entries.iter().map(|(key, _)| *key)),
+            serializer,
+        )?;
+
+        Ok(MapSortedResolver {
+            keys,
+            values,
+            index,
+        })
+    }
+}
+
+impl<K, V, H, D> DeserializeWith<MapSortedArchived<K::Archived, V::Archived>, HashMap<K, V, H>, D>
+    for MapSorted
+where
+    K: Archive + Hash + Eq,
+    K::Archived: Deserialize<K, D>,
+    V: Archive,
+    V::Archived: Deserialize<V, D>,
+    H: Default + BuildHasher,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &MapSortedArchived<K::Archived, V::Archived>,
+        deserializer: &mut D,
+    ) -> Result<HashMap<K, V, H>, D::Error> {
+        let mut map = HashMap::with_capacity_and_hasher(field.len(), H::default());
+        for (key, value) in field.iter() {
+            map.insert(
+                key.deserialize(deserializer)?,
+                value.deserialize(deserializer)?,
+            );
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MapSorted, MapSortedArchived};
+    use rkyv::{
+        Archive, Archived, Serialize, rancor::Error, string::ArchivedString, util::AlignedVec,
+    };
+    use std::collections::HashMap;
+    use test_case::test_case;
+
+    #[derive(Archive, Serialize)]
+    struct Container {
+        #[rkyv(with = MapSorted)]
+        map: HashMap<String, u32>,
+    }
+
+    fn serialized() -> AlignedVec {
+        let map = ["/a", "/b/c", "/b/d", "/bb", "/c"]
+            .into_iter()
+            .enumerate()
+            .map(|(index, key)| (key.to_owned(), index as u32))
+            .collect::<HashMap<_, _>>();
+
+        rkyv::to_bytes::<Error>(&Container { map }).unwrap()
+    }
+
+    fn map(serialized: &AlignedVec) -> &MapSortedArchived<ArchivedString, Archived<u32>> {
+        &rkyv::access::<ArchivedContainer, Error>(serialized)
+            .unwrap()
+            .map
+    }
+
+    #[test]
+    fn keys_are_sorted() {
+        let serialized = serialized();
+        let map = map(&serialized);
+
+        assert_eq!(
+            map.keys().map(|key| key.as_str()).collect::<Vec<_>>(),
+            ["/a", "/b/c", "/b/d", "/bb", "/c"]
+        );
+    }
+
+    #[test_case("/a", Some(0); "first")]
+    #[test_case("/b/d", Some(2); "middle")]
+    #[test_case("/c", Some(4); "last")]
+    #[test_case("/b", None; "missing prefix")]
+    #[test_case("/d", None; "missing after last")]
+    #[test_case("", None; "empty")]
+    fn get_returns_expected(
+        key: &str,
+        expected: Option<u32>,
+    ) {
+        let serialized = serialized();
+        let map = map(&serialized);
+
+        assert_eq!(map.get(key).map(|value| value.to_native()), expected);
+    }
+
+    #[test_case(0; "empty")]
+    #[test_case(1; "single")]
+    #[test_case(1000; "many")]
+    fn get_finds_all_keys(count: u32) {
+        let map = (0..count)
+            .map(|index| (format!("/file-{index}"), index))
+            .collect::<HashMap<_, _>>();
+        let serialized = rkyv::to_bytes::<Error>(&Container { map }).unwrap();
+        let map = self::map(&serialized);
+
+        assert_eq!(map.len(), count as usize);
+        for index in 0..count {
+            assert_eq!(
+                map.get(format!("/file-{index}").as_str())
+                    .map(|value| value.to_native()),
+                Some(index)
+            );
+        }
+        assert!(map.get("/file").is_none());
+    }
+
+    #[test_case("/b", &["/b/c", "/b/d", "/bb"]; "partial segment")]
+    #[test_case("/b/", &["/b/c", "/b/d"]; "directory")]
+    #[test_case("/c", &["/c"]; "exact")]
+    #[test_case("/d", &[]; "none")]
+    #[test_case("", &["/a", "/b/c", "/b/d", "/bb", "/c"]; "empty")]
+    fn iter_prefix_returns_expected(
+        prefix: &str,
+        expected: &[&str],
+    ) {
+        let serialized = serialized();
+        let map = map(&serialized);
+
+        assert_eq!(
+            map.iter_prefix(prefix)
+                .map(|(key, _value)| key.as_str())
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+}
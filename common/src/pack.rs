@@ -1,7 +1,7 @@
 //! Pack is the root entity, a collection of files.
 
-use crate::{file::File, pack_path::PackPath};
-use rkyv::{Archive, Serialize};
+use crate::{file::File, map_sorted::MapSorted, pack_path::PackPath};
+use rkyv::{Archive, Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Pack represents a group of files distinguished by their path.
@@ -16,11 +16,17 @@ use std::collections::HashMap;
 /// read/mmaped from fs) and served with
 /// [web-static-pack](https://crates.io/crates/web-static-pack)
 /// crate.
-#[derive(Archive, Serialize, Debug)]
+#[derive(Archive, Serialize, Deserialize, Debug)]
 #[rkyv(archived = PackArchived)]
 #[rkyv(derive(Debug))]
 #[rkyv(attr(allow(missing_docs)))] // TODO: resolve with https://github.com/rkyv/rkyv/issues/561
 pub struct Pack {
     /// List of contained files by their paths.
+    ///
+    /// Archived as an array sorted by path with a hash index (see
+    /// [MapSorted]). Lookups by path go through the hash index, prefix queries
+    /// are done with binary search and serialized [Pack] depends only on its
+    /// content.
+    #[rkyv(with = MapSorted)]
     pub files_by_path: HashMap<PackPath, File>,
 }
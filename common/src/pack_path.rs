@@ -1,6 +1,6 @@
 //! Pack path contains custom type for representing path inside a `pack`.
 
-use rkyv::{Archive, Serialize};
+use rkyv::{Archive, Deserialize, Serialize};
 use std::{borrow::Borrow, ops::Deref};
 
 /// [PackPath] represents path inside a `pack`. It will correspond to http
@@ -8,9 +8,9 @@ use std::{borrow::Borrow, ops::Deref};
 ///
 /// Custom type is used to enforce some rules, eg. starts with "/", contains
 /// only valid characters, etc.
-#[derive(Archive, Serialize, PartialEq, Eq, Hash, Debug)]
+#[derive(Archive, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 #[rkyv(archived = PackPathArchived)]
-#[rkyv(derive(PartialEq, Eq, Hash, Debug))]
+#[rkyv(derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug))]
 pub struct PackPath {
     inner: String,
 }
@@ -23,7 +23,7 @@ impl PackPath {
     }
 }
 
-// to allow searching in HashMap directly by http path (which is str)
+// to allow searching in maps directly by http path (which is str)
 impl Deref for PackPath {
     type Target = str;
 
@@ -0,0 +1,207 @@
+//! [actix-web](https://docs.rs/actix-web) integration, enabled with `actix`
+//! feature. Provides conversions of responder responses into [HttpResponse]
+//! and [scope] / [default_service] factories serving `pack`.
+//!
+//! actix-web uses its own http types, so headers are converted when passing
+//! requests and responses. Response content is not copied.
+
+use crate::{
+    pack::Pack,
+    responder::{ResponderOwned, ResponderRespondError, Response, ResponseOwned},
+};
+use actix_web::{
+    HttpRequest, HttpResponse, Responder as ActixResponder, Scope,
+    body::BoxBody,
+    dev::{ServiceFactory, ServiceRequest, ServiceResponse, fn_service},
+    http::{
+        StatusCode as ActixStatusCode,
+        header::{HeaderName as ActixHeaderName, HeaderValue as ActixHeaderValue},
+    },
+};
+use bytes::Bytes;
+use http::{HeaderMap, HeaderName, HeaderValue, Method, response::Parts};
+use std::future::ready;
+
+/// Converts response of [crate::responder::Responder] for `'static` `pack`
+/// (eg. included in executable) into [HttpResponse], without copying content.
+pub fn http_response_from_response(response: Response<'static>) -> HttpResponse {
+    let (parts, body) = response.into_parts();
+    http_response_build(parts, Bytes::from_static(body.data()))
+}
+
+/// Converts response of [ResponderOwned] into [HttpResponse], without copying
+/// content.
+pub fn http_response_from_response_owned(response: ResponseOwned) -> HttpResponse {
+    let (parts, body) = response.into_parts();
+    http_response_build(parts, body.into_bytes())
+}
+
+/// Builds [HttpResponse] from [http] response parts and body.
+fn http_response_build(
+    parts: Parts,
+    body: Bytes,
+) -> HttpResponse {
+    // status and headers are valid, as they are already in http
+    let mut http_response =
+        HttpResponse::build(ActixStatusCode::from_u16(parts.status.as_u16()).unwrap());
+    for (name, value) in parts.headers.iter() {
+        http_response.append_header((
+            ActixHeaderName::from_bytes(name.as_str().as_bytes()).unwrap(),
+            ActixHeaderValue::from_bytes(value.as_bytes()).unwrap(),
+        ));
+    }
+    http_response.body(body)
+}
+
+impl ActixResponder for ResponderRespondError {
+    type Body = BoxBody;
+
+    fn respond_to(
+        self,
+        _request: &HttpRequest,
+    ) -> HttpResponse<Self::Body> {
+        http_response_from_response(self.into_response())
+    }
+}
+
+/// Responds to actix request with [ResponderOwned::respond_request_flatten].
+///
+/// Path is taken from the unmatched part of request path, so inside a [Scope]
+/// mounted at `/static`, request to `/static/index.html` looks for
+/// `/index.html` in `pack`. As with [ResponderOwned::respond_request_flatten],
+/// path is percent-decoded and query string is handled according to
+/// [crate::responder::QueryPolicy].
+pub fn respond_flatten<P>(
+    responder: &ResponderOwned<P>,
+    request: &HttpRequest,
+) -> HttpResponse
+where
+    P: Pack + Send + Sync + 'static,
+{
+    // actix and http versions may differ, so everything is converted
+    // method and headers are valid, as they are already in actix
+    let method = Method::from_bytes(request.method().as_str().as_bytes()).unwrap();
+
+    let path = request.match_info().unprocessed();
+    let path = if path.is_empty() { "/" } else { path };
+
+    let headers = request
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            (
+                HeaderName::from_bytes(name.as_str().as_bytes()).unwrap(),
+                HeaderValue::from_bytes(value.as_bytes()).unwrap(),
+            )
+        })
+        .collect::<HeaderMap>();
+
+    let query = request.query_string();
+    let query = if query.is_empty() { None } else { Some(query) };
+
+    http_response_from_response_owned(
+        responder.respond_parts_flatten(&method, path, query, &headers),
+    )
+}
+
+/// Creates service factory serving `pack` for all requests, to be used as
+/// `App::default_service` or `Scope::default_service`.
+///
+/// # Examples
+///
+/// ```ignore
+/// let responder = ResponderOwned::new(Arc::new(pack));
+///
+/// HttpServer::new(move || {
+///     App::new()
+///         .route("/api/status", web::get().to(status))
+///         .default_service(default_service(responder.clone()))
+/// })
+/// ```
+pub fn default_service<P>(
+    responder: ResponderOwned<P>
+) -> impl ServiceFactory<
+    ServiceRequest,
+    Config = (),
+    Response = ServiceResponse,
+    Error = actix_web::Error,
+    InitError = (),
+> + 'static
+where
+    P: Pack + Send + Sync + 'static,
+{
+    fn_service(move |service_request: ServiceRequest| {
+        let response = respond_flatten(&responder, service_request.request());
+        ready(Ok::<_, actix_web::Error>(
+            service_request.into_response(response),
+        ))
+    })
+}
+
+/// Creates [Scope] mounted at `path`, serving `pack` for all requests inside
+/// it. Path inside `pack` is relative to scope `path`, see [respond_flatten].
+///
+/// # Examples
+///
+/// ```ignore
+/// let responder = ResponderOwned::new(Arc::new(pack));
+///
+/// HttpServer::new(move || {
+///     App::new().service(scope("/static", responder.clone()))
+/// })
+/// ```
+pub fn scope<P>(
+    path: &str,
+    responder: ResponderOwned<P>,
+) -> Scope
+where
+    P: Pack + Send + Sync + 'static,
+{
+    Scope::new(path).default_service(default_service(responder))
+}
+
+#[cfg(test)]
+mod test_actix {
+    use super::{http_response_from_response, http_response_from_response_owned};
+    use crate::{
+        body::{Body, BodyOwned},
+        responder::ResponderRespondError,
+    };
+    use actix_web::body::MessageBody;
+    use bytes::Bytes;
+    use http::{Response, StatusCode, header};
+
+    #[test]
+    fn converts_response() {
+        let response = Response::builder()
+            .status(StatusCode::OK)
+            .header(header::ETAG, "\"etagvalue\"")
+            .body(Body::new(b"content"))
+            .unwrap();
+
+        let http_response = http_response_from_response(response);
+        assert_eq!(http_response.status().as_u16(), 200);
+        assert_eq!(
+            http_response.headers().get("etag").unwrap().as_bytes(),
+            b"\"etagvalue\""
+        );
+        assert_eq!(
+            http_response.into_body().try_into_bytes().unwrap(),
+            Bytes::from_static(b"content")
+        );
+    }
+
+    #[test]
+    fn converts_response_owned() {
+        let response = ResponderRespondError::PackPathNotFound
+            .into_response_owned()
+            .map(|_body| BodyOwned::new(Bytes::from_static(b"content")));
+
+        let http_response = http_response_from_response_owned(response);
+        assert_eq!(http_response.status().as_u16(), 404);
+        assert_eq!(
+            http_response.into_body().try_into_bytes().unwrap(),
+            Bytes::from_static(b"content")
+        );
+    }
+}
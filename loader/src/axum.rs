@@ -0,0 +1,127 @@
+//! [axum](https://docs.rs/axum) integration, enabled with `axum` feature.
+//! Provides [router] and [IntoResponse] implementations for responder
+//! responses and errors.
+//!
+//! [crate::responder::ResponseOwned] already implements [IntoResponse], so
+//! result of [ResponderOwned::respond] can be returned from handlers directly.
+//! For putting `pack` in front of other routes see
+//! [crate::tower::ResponderLayer].
+
+use crate::{
+    body::{Body, BodyOwned},
+    pack::Pack,
+    responder::{ResponderOwned, ResponderRespondError},
+    tower::ResponderService,
+};
+use axum::{
+    Router,
+    body::Body as AxumBody,
+    response::{IntoResponse, Response},
+};
+use bytes::Bytes;
+
+/// Creates [Router] serving `pack` from [ResponderOwned] for all requests, as
+/// a fallback service. Other routes can be added to the router, they take
+/// precedence over `pack` files.
+///
+/// # Examples
+///
+/// ```ignore
+/// let pack = PackOwned::read_file(...).unwrap();
+///
+/// let router = router(ResponderOwned::new(Arc::new(pack)))
+///     .route("/api/status", get(status));
+///
+/// axum::serve(listener, router).await.unwrap();
+/// ```
+pub fn router<P, S>(responder: ResponderOwned<P>) -> Router<S>
+where
+    P: Pack + Send + Sync + 'static,
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new().fallback_service(ResponderService::new(responder))
+}
+
+impl IntoResponse for ResponderRespondError {
+    fn into_response(self) -> Response {
+        (self.status_code(), self.headers()).into_response()
+    }
+}
+
+impl IntoResponse for BodyOwned {
+    fn into_response(self) -> Response {
+        Response::new(AxumBody::new(self))
+    }
+}
+
+/// Body of `pack` included in executable, eg. from [crate::loader::load] on
+/// `'static` bytes. Content is not copied.
+impl IntoResponse for Body<'static> {
+    fn into_response(self) -> Response {
+        BodyOwned::new(Bytes::from_static(self.data())).into_response()
+    }
+}
+
+#[cfg(test)]
+mod test_axum {
+    use super::router;
+    use crate::{
+        body::Body,
+        common::pack::Pack,
+        responder::{ResponderOwned, ResponderRespondError},
+        test_fixture::pack_present,
+    };
+    use axum::{body::Body as AxumBody, http::Request, response::IntoResponse, routing::get};
+    use http::{StatusCode, header};
+    use http_body_util::BodyExt;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn responder() -> ResponderOwned<Pack> {
+        let pack = pack_present();
+        ResponderOwned::new(Arc::new(pack))
+    }
+
+    async fn body_collect(body: AxumBody) -> Vec<u8> {
+        body.collect().await.unwrap().to_bytes().to_vec()
+    }
+
+    #[tokio::test]
+    async fn router_serves_pack_and_routes() {
+        let router = router(responder()).route("/api/status", get(|| async { "ok" }));
+
+        let response = router
+            .clone()
+            .oneshot(Request::get("/present").body(AxumBody::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            body_collect(response.into_body()).await,
+            b"content-identity"
+        );
+
+        let response = router
+            .clone()
+            .oneshot(Request::get("/api/status").body(AxumBody::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(body_collect(response.into_body()).await, b"ok");
+
+        let response = router
+            .oneshot(Request::get("/missing").body(AxumBody::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn responses_convert_into_response() {
+        let response = ResponderRespondError::HttpMethodNotSupported.into_response();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(response.headers()[header::ALLOW], "GET, HEAD, OPTIONS");
+
+        let response = Body::new(b"content").into_response();
+        assert_eq!(body_collect(response.into_body()).await, b"content");
+    }
+}
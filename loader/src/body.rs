@@ -1,6 +1,7 @@
 //! [http] / [http_body] crate abstractions. Provides [Body], implementing
-//! [HttpBody] for raw bytes slice.
+//! [HttpBody] for raw bytes slice and [BodyOwned], implementing it for [Bytes].
 
+use bytes::Bytes;
 use http_body::{Body as HttpBody, Frame, SizeHint};
 use std::{
     convert::Infallible,
@@ -68,11 +69,76 @@ impl<'a> HttpBody for Body<'a> {
     }
 }
 
+/// Like [Body], but holding [Bytes] instead of borrowed slice, so it is
+/// `'static` and can be moved between tasks / threads.
+///
+/// Used by [crate::responder::ResponderOwned], where [Bytes] point directly
+/// into `pack` memory and keep the `pack` alive.
+#[derive(Debug)]
+pub struct BodyOwned {
+    // None(empty bytes) is not allowed.
+    data: Option<Bytes>,
+}
+impl BodyOwned {
+    /// Creates [self] from data.
+    pub fn new(data: Bytes) -> Self {
+        let data = if !data.is_empty() { Some(data) } else { None };
+        Self { data }
+    }
+
+    /// Creates empty [self].
+    pub fn empty() -> Self {
+        let data = None;
+        Self { data }
+    }
+
+    /// Returns remaining data.
+    ///
+    /// This will return original content until polled with [Self::poll_frame],
+    /// then it will return empty slice.
+    pub fn data(&self) -> &[u8] {
+        self.data.as_deref().unwrap_or(b"")
+    }
+
+    /// Returns remaining data, consuming [self].
+    pub fn into_bytes(self) -> Bytes {
+        self.data.unwrap_or_default()
+    }
+}
+impl HttpBody for BodyOwned {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let data = self.get_mut().data.take();
+
+        match data {
+            Some(data) => Poll::Ready(Some(Ok(Frame::data(data)))),
+            None => Poll::Ready(None),
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.data.is_none()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match &self.data {
+            Some(data) => SizeHint::with_exact(data.len() as u64),
+            None => SizeHint::with_exact(0),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test_body {
-    use super::Body as Body_;
+    use super::{Body as Body_, BodyOwned};
+    use bytes::Bytes;
     use http_body::Body;
-    use http_body_util::combinators::BoxBody;
+    use http_body_util::{BodyExt, combinators::BoxBody};
 
     /// we want to keep our body to be compatible with [BoxBody]
     #[test]
@@ -82,4 +148,16 @@ mod test_body {
 
         assert_eq!(box_body.size_hint().lower(), 3);
     }
+
+    #[tokio::test]
+    async fn body_owned_yields_data_once() {
+        let body = BodyOwned::new(Bytes::from_static(b"foo"));
+        assert_eq!(body.size_hint().exact(), Some(3));
+        assert!(!body.is_end_stream());
+
+        let data = body.collect().await.unwrap().to_bytes();
+        assert_eq!(&*data, b"foo");
+
+        assert!(BodyOwned::new(Bytes::new()).is_end_stream());
+    }
 }
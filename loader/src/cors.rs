@@ -0,0 +1,344 @@
+//! Cross-origin resource sharing (CORS) handling. Contains [CorsPolicy], used
+//! by [crate::responder::Responder] to add `access-control-*` headers to
+//! responses and answer preflight requests.
+
+use http::{HeaderMap, HeaderName, HeaderValue, header};
+use std::time::Duration;
+
+/// Origins allowed by [CorsPolicy].
+#[derive(Debug)]
+pub enum CorsOrigins {
+    /// Any origin is allowed, `*` is sent in `access-control-allow-origin`.
+    /// With [CorsPolicy::allow_credentials] set, `*` is not accepted by
+    /// browsers, so request origin is sent back instead.
+    Any,
+    /// Only listed origins (eg. `https://app.example.com`, without trailing
+    /// slash) are allowed. Matching origin is sent back in
+    /// `access-control-allow-origin`.
+    List(Vec<String>),
+}
+
+/// CORS policy for [crate::responder::Responder].
+///
+/// `GET` and `HEAD` responses for requests with allowed `origin` get
+/// `access-control-allow-origin` header, along with
+/// `access-control-allow-credentials` and `access-control-expose-headers` if
+/// configured.
+///
+/// Preflight request (`OPTIONS` with `origin` and
+/// `access-control-request-method` headers) for allowed origin and `GET` or
+/// `HEAD` method is answered with `access-control-allow-*` headers. Requested
+/// headers (`access-control-request-headers`) are all allowed, as `pack` files
+/// do not depend on them. Preflight requests not matching the policy are
+/// answered without CORS headers, so browser will block the actual request.
+///
+/// When request origin is sent back in `access-control-allow-origin` (see
+/// [Self::origin_reflected]), all responses get `vary: origin` header, so
+/// caches do not reuse response for one origin with another.
+#[derive(Debug)]
+pub struct CorsPolicy {
+    /// Origins allowed to access `pack` files.
+    pub allowed_origins: CorsOrigins,
+
+    /// Whether requests with credentials (cookies, http authentication) are
+    /// allowed, sent as `access-control-allow-credentials: true`.
+    pub allow_credentials: bool,
+
+    /// Response headers readable by cross-origin scripts in addition to the
+    /// safelisted ones, sent in `access-control-expose-headers`, eg. `etag`.
+    pub expose_headers: Vec<HeaderName>,
+
+    /// How long browser may cache preflight response, sent in
+    /// `access-control-max-age`. [None] leaves it to the browser default.
+    pub max_age: Option<Duration>,
+}
+impl CorsPolicy {
+    /// Whether request origin is sent back in `access-control-allow-origin`
+    /// (instead of `*`), making responses dependent on `origin` header.
+    pub fn origin_reflected(&self) -> bool {
+        match &self.allowed_origins {
+            CorsOrigins::Any => self.allow_credentials,
+            CorsOrigins::List(_) => true,
+        }
+    }
+
+    /// Returns `access-control-allow-origin` value for request `origin`, or
+    /// [None] if `origin` is not allowed.
+    pub fn allow_origin(
+        &self,
+        origin: &HeaderValue,
+    ) -> Option<HeaderValue> {
+        match &self.allowed_origins {
+            CorsOrigins::Any if self.allow_credentials => Some(origin.clone()),
+            CorsOrigins::Any => Some(HeaderValue::from_static("*")),
+            CorsOrigins::List(origins) => origins
+                .iter()
+                .any(|allowed_origin| allowed_origin.as_bytes() == origin.as_bytes())
+                .then(|| origin.clone()),
+        }
+    }
+
+    /// Returns headers to be added to `GET` or `HEAD` response for request
+    /// with `request_headers`. Only `vary` (if needed) is returned if request
+    /// has no `origin` or it's not allowed by this policy.
+    pub fn response_headers(
+        &self,
+        request_headers: &HeaderMap,
+    ) -> HeaderMap {
+        let mut headers = self.headers_vary();
+
+        let origin = match request_headers.get(header::ORIGIN) {
+            Some(origin) => origin,
+            None => return headers,
+        };
+
+        let allow_origin = match self.allow_origin(origin) {
+            Some(allow_origin) => allow_origin,
+            None => return headers,
+        };
+
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+        if self.allow_credentials {
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+        if !self.expose_headers.is_empty() {
+            let expose_headers = self
+                .expose_headers
+                .iter()
+                .map(HeaderName::as_str)
+                .collect::<Vec<_>>()
+                .join(", ");
+            // header names are valid header values
+            let expose_headers = HeaderValue::from_str(&expose_headers).unwrap();
+            headers.insert(header::ACCESS_CONTROL_EXPOSE_HEADERS, expose_headers);
+        }
+
+        headers
+    }
+
+    /// Returns headers to be added to response for preflight request with
+    /// `request_headers`. Only `vary` (if needed) is returned if request is
+    /// not a preflight or is not allowed by this policy.
+    pub fn preflight_headers(
+        &self,
+        request_headers: &HeaderMap,
+    ) -> HeaderMap {
+        let mut headers = self.headers_vary();
+
+        let origin = match request_headers.get(header::ORIGIN) {
+            Some(origin) => origin,
+            None => return headers,
+        };
+        let request_method = match request_headers.get(header::ACCESS_CONTROL_REQUEST_METHOD) {
+            Some(request_method) => request_method,
+            None => return headers,
+        };
+
+        // only methods served by responder can be allowed
+        if !matches!(request_method.as_bytes(), b"GET" | b"HEAD") {
+            return headers;
+        }
+
+        let allow_origin = match self.allow_origin(origin) {
+            Some(allow_origin) => allow_origin,
+            None => return headers,
+        };
+
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+        headers.insert(
+            header::ACCESS_CONTROL_ALLOW_METHODS,
+            HeaderValue::from_static("GET, HEAD"),
+        );
+        if self.allow_credentials {
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+        if let Some(request_headers) = request_headers.get(header::ACCESS_CONTROL_REQUEST_HEADERS) {
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_HEADERS,
+                request_headers.clone(),
+            );
+        }
+        if let Some(max_age) = self.max_age {
+            headers.insert(header::ACCESS_CONTROL_MAX_AGE, max_age.as_secs().into());
+        }
+
+        headers
+    }
+
+    /// Returns map with `vary: origin` if origin is reflected, empty
+    /// otherwise.
+    fn headers_vary(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if self.origin_reflected() {
+            headers.insert(header::VARY, HeaderValue::from_static("origin"));
+        }
+        headers
+    }
+}
+
+#[cfg(test)]
+mod test_cors {
+    use super::{CorsOrigins, CorsPolicy};
+    use http::{HeaderMap, HeaderValue, header};
+    use std::time::Duration;
+    use test_case::test_case;
+
+    fn cors_policy_list() -> CorsPolicy {
+        CorsPolicy {
+            allowed_origins: CorsOrigins::List(vec!["https://app.example.com".to_owned()]),
+            allow_credentials: false,
+            expose_headers: Vec::new(),
+            max_age: None,
+        }
+    }
+
+    fn request_headers(
+        origin: Option<&'static str>,
+        request_method: Option<&'static str>,
+    ) -> HeaderMap {
+        let mut request_headers = HeaderMap::new();
+        if let Some(origin) = origin {
+            request_headers.insert(header::ORIGIN, HeaderValue::from_static(origin));
+        }
+        if let Some(request_method) = request_method {
+            request_headers.insert(
+                header::ACCESS_CONTROL_REQUEST_METHOD,
+                HeaderValue::from_static(request_method),
+            );
+        }
+        request_headers
+    }
+
+    #[test_case(CorsOrigins::Any, false, "https://other.example.com", Some("*"); "any")]
+    #[test_case(CorsOrigins::Any, true, "https://other.example.com", Some("https://other.example.com"); "any with credentials")]
+    #[test_case(cors_policy_list().allowed_origins, false, "https://app.example.com", Some("https://app.example.com"); "listed")]
+    #[test_case(cors_policy_list().allowed_origins, false, "https://other.example.com", None; "not listed")]
+    #[test_case(cors_policy_list().allowed_origins, false, "https://app.example.com:8080", None; "different port")]
+    fn allow_origin_returns_expected(
+        allowed_origins: CorsOrigins,
+        allow_credentials: bool,
+        origin: &'static str,
+        expected: Option<&str>,
+    ) {
+        let cors_policy = CorsPolicy {
+            allowed_origins,
+            allow_credentials,
+            ..cors_policy_list()
+        };
+
+        let allow_origin = cors_policy.allow_origin(&HeaderValue::from_static(origin));
+        assert_eq!(
+            allow_origin.as_ref().map(|value| value.to_str().unwrap()),
+            expected
+        );
+    }
+
+    #[test]
+    fn response_headers_allows_request() {
+        let cors_policy = CorsPolicy {
+            allow_credentials: true,
+            expose_headers: vec![header::ETAG, header::CONTENT_LENGTH],
+            ..cors_policy_list()
+        };
+
+        let headers =
+            cors_policy.response_headers(&request_headers(Some("https://app.example.com"), None));
+
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://app.example.com"
+        );
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_EXPOSE_HEADERS],
+            "etag, content-length"
+        );
+        assert_eq!(headers[header::VARY], "origin");
+    }
+
+    #[test_case(CorsOrigins::Any, None, None; "any without origin")]
+    #[test_case(CorsOrigins::Any, Some("https://other.example.com"), Some("*"); "any")]
+    #[test_case(cors_policy_list().allowed_origins, None, None; "list without origin")]
+    #[test_case(cors_policy_list().allowed_origins, Some("https://other.example.com"), None; "list not listed")]
+    fn response_headers_returns_expected(
+        allowed_origins: CorsOrigins,
+        origin: Option<&'static str>,
+        expected: Option<&str>,
+    ) {
+        let cors_policy = CorsPolicy {
+            allowed_origins,
+            ..cors_policy_list()
+        };
+
+        let headers = cors_policy.response_headers(&request_headers(origin, None));
+
+        assert_eq!(
+            headers
+                .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                .map(|value| value.to_str().unwrap()),
+            expected
+        );
+        assert!(
+            headers
+                .get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS)
+                .is_none()
+        );
+        assert!(headers.get(header::ACCESS_CONTROL_EXPOSE_HEADERS).is_none());
+
+        // `vary` depends only on policy, not on request
+        assert_eq!(
+            headers.contains_key(header::VARY),
+            cors_policy.origin_reflected()
+        );
+    }
+
+    #[test]
+    fn preflight_headers_allows_preflight() {
+        let cors_policy = CorsPolicy {
+            allow_credentials: true,
+            max_age: Some(Duration::from_secs(600)),
+            ..cors_policy_list()
+        };
+
+        let mut request_headers = request_headers(Some("https://app.example.com"), Some("GET"));
+        request_headers.insert(
+            header::ACCESS_CONTROL_REQUEST_HEADERS,
+            HeaderValue::from_static("x-requested-with"),
+        );
+        let headers = cors_policy.preflight_headers(&request_headers);
+
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://app.example.com"
+        );
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_METHODS], "GET, HEAD");
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_HEADERS],
+            "x-requested-with"
+        );
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert_eq!(headers[header::ACCESS_CONTROL_MAX_AGE], "600");
+        assert_eq!(headers[header::VARY], "origin");
+    }
+
+    #[test_case("https://app.example.com", None; "not preflight")]
+    #[test_case("https://app.example.com", Some("POST"); "method not allowed")]
+    #[test_case("https://other.example.com", Some("GET"); "origin not allowed")]
+    fn preflight_headers_rejects(
+        origin: &'static str,
+        request_method: Option<&'static str>,
+    ) {
+        let headers =
+            cors_policy_list().preflight_headers(&request_headers(Some(origin), request_method));
+
+        assert!(headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+        assert!(headers.get(header::ACCESS_CONTROL_ALLOW_METHODS).is_none());
+        assert_eq!(headers[header::VARY], "origin");
+    }
+}
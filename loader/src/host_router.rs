@@ -0,0 +1,502 @@
+//! Virtual-host routing. Provides [HostRouter], dispatching requests to one of
+//! multiple [Responder]s based on `host` header.
+
+use crate::{
+    body::Body,
+    pack::Pack,
+    responder::{Responder, ResponderRespondError, Response},
+};
+use anyhow::{Error, ensure};
+use http::{HeaderMap, Method, StatusCode, header, response::Builder as ResponseBuilder};
+use std::{net::Ipv6Addr, str::FromStr};
+
+/// Pattern matched against `host` header by [HostRouter].
+///
+/// Patterns are created from string representation with [FromStr]:
+/// - `example.com` creates [HostPattern::Exact], matching only `example.com`.
+/// - `*.example.com` creates [HostPattern::Wildcard], matching any subdomain
+///   (eg. `admin.example.com` or `eu.admin.example.com`), but not `example.com`
+///   itself.
+/// - `[::1]` (ipv6 literal in brackets, as in `host` header) creates
+///   [HostPattern::Exact]. Address is compared textually, so it should be
+///   written the same way clients send it.
+///
+/// Matching is case insensitive, port (if present in `host` header) is
+/// ignored. Patterns are normalized like hosts (see [host_normalize]), so
+/// trailing dot is ignored, but must not contain port.
+#[derive(PartialEq, Eq, Debug)]
+pub enum HostPattern {
+    /// Matches single host, eg. `example.com`. Stored lowercase.
+    Exact(String),
+    /// Matches all subdomains of given domain. Stored lowercase, without `*.`
+    /// prefix, eg. `example.com` for `*.example.com`.
+    Wildcard(String),
+}
+impl HostPattern {
+    /// Checks if (normalized, as by [host_normalize]) `host` matches this
+    /// pattern.
+    pub fn matches(
+        &self,
+        host: &str,
+    ) -> bool {
+        match self {
+            HostPattern::Exact(pattern) => host == pattern,
+            HostPattern::Wildcard(pattern) => host
+                .strip_suffix(pattern.as_str())
+                .and_then(|subdomain| subdomain.strip_suffix('.'))
+                .is_some_and(|subdomain| !subdomain.is_empty()),
+        }
+    }
+}
+impl FromStr for HostPattern {
+    type Err = Error;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        let (pattern, wildcard) = match pattern.strip_prefix("*.") {
+            Some(pattern) => (pattern, true),
+            None => (pattern, false),
+        };
+
+        ensure!(!pattern.is_empty(), "host pattern must not be empty");
+
+        // ipv6 literal, eg. `[::1]`, contains colons, but may not be a wildcard
+        let ipv6 = match pattern
+            .strip_prefix('[')
+            .and_then(|pattern| pattern.strip_suffix(']'))
+        {
+            Some(address) => {
+                ensure!(
+                    address.parse::<Ipv6Addr>().is_ok(),
+                    "host pattern in brackets must be a valid ipv6 address"
+                );
+                ensure!(!wildcard, "ipv6 host pattern must not be a wildcard");
+                true
+            }
+            None => false,
+        };
+        ensure!(
+            ipv6 || !pattern.contains(['*', ':', '/']),
+            "host pattern may contain wildcard only as leading `*.`, and must not contain port"
+        );
+
+        // normalized the same way as request hosts, so they can match
+        let pattern = host_normalize(pattern);
+        ensure!(!pattern.is_empty(), "host pattern must not be empty");
+
+        let self_ = if wildcard {
+            HostPattern::Wildcard(pattern)
+        } else {
+            HostPattern::Exact(pattern)
+        };
+
+        Ok(self_)
+    }
+}
+
+/// Normalizes `host` header value for matching with [HostPattern], by removing
+/// port, trailing dot and converting to lowercase.
+pub fn host_normalize(host: &str) -> String {
+    // ipv6 literals are enclosed in brackets, port is after closing one
+    let host = match host.rsplit_once(':') {
+        Some((host_without_port, port))
+            if !host_without_port.is_empty()
+                && !port.is_empty()
+                && port.bytes().all(|byte| byte.is_ascii_digit())
+                && (!host_without_port.contains(':') || host_without_port.ends_with(']')) =>
+        {
+            host_without_port
+        }
+        _ => host,
+    };
+    let host = host.strip_suffix('.').unwrap_or(host);
+
+    host.to_ascii_lowercase()
+}
+
+/// Router, selecting [Responder] (and so the [Pack]) by request `host` header.
+///
+/// Routes are checked in two passes: first all [HostPattern::Exact] patterns,
+/// then [HostPattern::Wildcard] (in order they were provided). If no route
+/// matches (or `host` header is missing), the default [Responder] is used, if
+/// set.
+///
+/// Please note that http/2 clients may send host in `:authority`
+/// pseudo-header (accessible through request uri) instead of `host` header.
+/// Most servers (including hyper) do not copy it into headers, so for http/2 it
+/// may need to be copied by the user.
+///
+/// # Examples
+///
+/// ```ignore
+/// let pack_admin = web_static_pack::loader::load(...).unwrap();
+/// let pack_public = web_static_pack::loader::load(...).unwrap();
+///
+/// let host_router = HostRouter::new(
+///     Box::new([
+///         ("admin.example.com".parse().unwrap(), Responder::new(pack_admin)),
+///         ("*.example.com".parse().unwrap(), Responder::new(pack_public)),
+///     ]),
+///     Some(Responder::new(pack_public)),
+/// );
+///
+/// assert_eq!(
+///     host_router.respond_flatten(
+///         &Method::GET,
+///         "/index.html",
+///         &[(header::HOST, HeaderValue::from_static("admin.example.com"))]
+///             .into_iter()
+///             .collect::<HeaderMap>(),
+///     ).status(),
+///     StatusCode::OK
+/// );
+/// ```
+#[derive(Debug)]
+pub struct HostRouter<'p, P>
+where
+    P: Pack,
+{
+    routes: Box<[(HostPattern, Responder<'p, P>)]>,
+    default: Option<Responder<'p, P>>,
+}
+impl<'p, P> HostRouter<'p, P>
+where
+    P: Pack,
+{
+    /// Creates new instance from list of routes and optional default
+    /// [Responder], used when no route matches.
+    pub fn new(
+        routes: Box<[(HostPattern, Responder<'p, P>)]>,
+        default: Option<Responder<'p, P>>,
+    ) -> Self {
+        Self { routes, default }
+    }
+
+    /// Finds [Responder] for given `host` header value. Falls back to default
+    /// [Responder] if none of routes matches.
+    pub fn get_responder_by_host(
+        &self,
+        host: Option<&str>,
+    ) -> Option<&Responder<'p, P>> {
+        let responder = host
+            .map(host_normalize)
+            .and_then(|host| {
+                let routes_exact = self.routes.iter().filter(|(host_pattern, _responder)| {
+                    matches!(host_pattern, HostPattern::Exact(_))
+                });
+                let routes_wildcard = self.routes.iter().filter(|(host_pattern, _responder)| {
+                    matches!(host_pattern, HostPattern::Wildcard(_))
+                });
+
+                routes_exact
+                    .chain(routes_wildcard)
+                    .find(|(host_pattern, _responder)| host_pattern.matches(&host))
+                    .map(|(_host_pattern, responder)| responder)
+            })
+            .or(self.default.as_ref());
+
+        responder
+    }
+
+    /// Returns http response for given request parts or rust error to be
+    /// handled by user.
+    ///
+    /// Selects [Responder] with [Self::get_responder_by_host] using `host`
+    /// header and passes request to its [Responder::respond].
+    pub fn respond(
+        &self,
+        method: &Method,
+        path: &str,
+        headers: &HeaderMap,
+    ) -> Result<Response<'p>, HostRouterRespondError> {
+        let host = match headers.get(header::HOST) {
+            Some(host) => match host.to_str() {
+                Ok(host) => Some(host),
+                Err(_) => return Err(HostRouterRespondError::UnparsableHost),
+            },
+            None => None,
+        };
+
+        let responder = match self.get_responder_by_host(host) {
+            Some(responder) => responder,
+            None => return Err(HostRouterRespondError::HostNotMatched),
+        };
+
+        let response = responder
+            .respond(method, path, headers)
+            .map_err(HostRouterRespondError::Responder)?;
+
+        Ok(response)
+    }
+
+    /// Like [Self::respond], but generates "default" (proper http status code
+    /// and empty body) responses also for errors.
+    pub fn respond_flatten(
+        &self,
+        method: &Method,
+        path: &str,
+        headers: &HeaderMap,
+    ) -> Response<'p> {
+        match self.respond(method, path, headers) {
+            Ok(response) => response,
+            Err(host_router_error) => host_router_error.into_response(),
+        }
+    }
+}
+
+/// Possible errors during [HostRouter::respond] handling.
+#[derive(PartialEq, Eq, Debug)]
+pub enum HostRouterRespondError {
+    /// `host` header contains invalid characters. This maps to HTTP
+    /// `BAD_REQUEST`.
+    UnparsableHost,
+
+    /// No route matches request `host` and no default was set. This maps to
+    /// HTTP `MISDIRECTED_REQUEST`.
+    HostNotMatched,
+
+    /// Error returned by selected [Responder].
+    Responder(ResponderRespondError),
+}
+impl HostRouterRespondError {
+    /// Converts error into best matching HTTP error code.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            HostRouterRespondError::UnparsableHost => StatusCode::BAD_REQUEST,
+            HostRouterRespondError::HostNotMatched => StatusCode::MISDIRECTED_REQUEST,
+            HostRouterRespondError::Responder(responder_error) => responder_error.status_code(),
+        }
+    }
+
+    /// Creates default response (status code + required headers + empty body)
+    /// for this error. [Responder] errors are converted with
+    /// [ResponderRespondError::into_response].
+    pub fn into_response(&self) -> Response<'static> {
+        if let HostRouterRespondError::Responder(responder_error) = self {
+            return responder_error.into_response();
+        }
+
+        let response = ResponseBuilder::new()
+            .status(self.status_code())
+            .body(Body::empty())
+            .unwrap();
+        response
+    }
+}
+
+#[cfg(test)]
+mod test_host_pattern {
+    use super::{HostPattern, host_normalize};
+    use test_case::test_case;
+
+    #[test_case("example.com", Some(HostPattern::Exact("example.com".to_owned())); "exact")]
+    #[test_case("Admin.Example.COM", Some(HostPattern::Exact("admin.example.com".to_owned())); "exact uppercase")]
+    #[test_case("*.example.com", Some(HostPattern::Wildcard("example.com".to_owned())); "wildcard")]
+    #[test_case("", None; "empty")]
+    #[test_case("*.", None; "empty wildcard")]
+    #[test_case("admin.*.com", None; "wildcard in the middle")]
+    #[test_case("example.com:8080", None; "with port")]
+    #[test_case("Example.com:443", None; "with port uppercase")]
+    #[test_case("Example.com.", Some(HostPattern::Exact("example.com".to_owned())); "exact trailing dot")]
+    #[test_case("*.example.com.", Some(HostPattern::Wildcard("example.com".to_owned())); "wildcard trailing dot")]
+    #[test_case(".", None; "only dot")]
+    #[test_case("[::1]", Some(HostPattern::Exact("[::1]".to_owned())); "ipv6")]
+    #[test_case("[2001:DB8::1]", Some(HostPattern::Exact("[2001:db8::1]".to_owned())); "ipv6 uppercase")]
+    #[test_case("[::1]:8080", None; "ipv6 with port")]
+    #[test_case("[example.com]", None; "invalid ipv6")]
+    #[test_case("*.[::1]", None; "ipv6 wildcard")]
+    fn from_str_returns_expected(
+        pattern: &str,
+        expected: Option<HostPattern>,
+    ) {
+        assert_eq!(pattern.parse::<HostPattern>().ok(), expected);
+    }
+
+    #[test_case("example.com", "example.com", true)]
+    #[test_case("example.com", "admin.example.com", false)]
+    #[test_case("*.example.com", "admin.example.com", true)]
+    #[test_case("*.example.com", "eu.admin.example.com", true)]
+    #[test_case("*.example.com", "example.com", false)]
+    #[test_case("*.example.com", "badexample.com", false)]
+    #[test_case("[::1]", "[::1]", true)]
+    #[test_case("Example.com.", &host_normalize("example.COM.:8080"), true)]
+    fn matches_returns_expected(
+        pattern: &str,
+        host: &str,
+        expected: bool,
+    ) {
+        assert_eq!(
+            pattern.parse::<HostPattern>().unwrap().matches(host),
+            expected
+        );
+    }
+
+    #[test_case("example.com", "example.com"; "already normalized")]
+    #[test_case("Example.COM:8080", "example.com"; "uppercase with port")]
+    #[test_case("example.com.", "example.com"; "trailing dot")]
+    #[test_case("[::1]:8080", "[::1]"; "ipv6 with port")]
+    #[test_case("[::1]", "[::1]"; "ipv6 without port")]
+    fn host_normalize_returns_expected(
+        host: &str,
+        expected: &str,
+    ) {
+        assert_eq!(host_normalize(host), expected);
+    }
+}
+
+#[cfg(test)]
+mod test_host_router {
+    use super::{HostRouter, HostRouterRespondError};
+    use crate::{
+        cache_control::CacheControl,
+        file::File,
+        pack::Pack,
+        responder::{Responder, ResponderRespondError},
+    };
+    use http::{HeaderMap, HeaderValue, Method, StatusCode, header};
+    use test_case::test_case;
+
+    struct FileMock {
+        content: &'static [u8],
+    }
+    impl File for FileMock {
+        fn content(&self) -> &[u8] {
+            self.content
+        }
+        fn content_gzip(&self) -> Option<&[u8]> {
+            None
+        }
+        fn content_brotli(&self) -> Option<&[u8]> {
+            None
+        }
+
+        fn content_type(&self) -> HeaderValue {
+            HeaderValue::from_static("text/plain; charset=utf-8")
+        }
+        fn etag(&self) -> HeaderValue {
+            HeaderValue::from_static("\"etagvalue\"")
+        }
+        fn cache_control(&self) -> CacheControl {
+            CacheControl::NoCache
+        }
+    }
+
+    struct PackMock {
+        file: FileMock,
+    }
+    impl Pack for PackMock {
+        type File = FileMock;
+
+        fn get_file_by_path(
+            &self,
+            path: &str,
+        ) -> Option<&Self::File> {
+            match path {
+                "/present" => Some(&self.file),
+                _ => None,
+            }
+        }
+    }
+
+    static PACK_ADMIN: PackMock = PackMock {
+        file: FileMock { content: b"admin" },
+    };
+    static PACK_PUBLIC: PackMock = PackMock {
+        file: FileMock { content: b"public" },
+    };
+    static PACK_DEFAULT: PackMock = PackMock {
+        file: FileMock {
+            content: b"default",
+        },
+    };
+
+    fn host_router(default: bool) -> HostRouter<'static, PackMock> {
+        HostRouter::new(
+            Box::new([
+                // wildcard is listed first, but exact should still win
+                (
+                    "*.example.com".parse().unwrap(),
+                    Responder::new(&PACK_PUBLIC),
+                ),
+                (
+                    "admin.example.com".parse().unwrap(),
+                    Responder::new(&PACK_ADMIN),
+                ),
+            ]),
+            default.then(|| Responder::new(&PACK_DEFAULT)),
+        )
+    }
+
+    fn headers_with_host(host: Option<&'static str>) -> HeaderMap {
+        host.into_iter()
+            .map(|host| (header::HOST, HeaderValue::from_static(host)))
+            .collect::<HeaderMap>()
+    }
+
+    #[test_case(Some("admin.example.com"), true, Ok(b"admin".as_slice()); "exact match")]
+    #[test_case(Some("ADMIN.example.com:8080"), true, Ok(b"admin".as_slice()); "exact match with port and case")]
+    #[test_case(Some("www.example.com"), true, Ok(b"public".as_slice()); "wildcard match")]
+    #[test_case(Some("other.com"), true, Ok(b"default".as_slice()); "fallback to default")]
+    #[test_case(None, true, Ok(b"default".as_slice()); "missing host fallback to default")]
+    #[test_case(Some("other.com"), false, Err(HostRouterRespondError::HostNotMatched); "no default")]
+    fn respond_returns_expected(
+        host: Option<&'static str>,
+        default: bool,
+        expected: Result<&[u8], HostRouterRespondError>,
+    ) {
+        let host_router = host_router(default);
+
+        let response = host_router.respond(&Method::GET, "/present", &headers_with_host(host));
+
+        assert_eq!(response.map(|response| response.body().data()), expected);
+    }
+
+    #[test]
+    fn resolves_error_for_file_not_found() {
+        let host_router = host_router(true);
+
+        let response_error = host_router
+            .respond(
+                &Method::GET,
+                "/missing",
+                &headers_with_host(Some("admin.example.com")),
+            )
+            .unwrap_err();
+        assert_eq!(
+            response_error,
+            HostRouterRespondError::Responder(ResponderRespondError::PackPathNotFound)
+        );
+
+        let response_flatten = response_error.into_response();
+        assert_eq!(response_flatten.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn resolves_error_for_invalid_method() {
+        let host_router = host_router(true);
+
+        let response_flatten = host_router.respond_flatten(
+            &Method::POST,
+            "/present",
+            &headers_with_host(Some("admin.example.com")),
+        );
+        assert_eq!(response_flatten.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            response_flatten.headers()[header::ALLOW],
+            "GET, HEAD, OPTIONS"
+        );
+    }
+
+    #[test]
+    fn resolves_error_for_unparsable_host() {
+        let host_router = host_router(true);
+
+        let response_flatten = host_router.respond_flatten(
+            &Method::GET,
+            "/present",
+            &[(header::HOST, HeaderValue::from_bytes(b"\xff").unwrap())]
+                .into_iter()
+                .collect::<HeaderMap>(),
+        );
+        assert_eq!(response_flatten.status(), StatusCode::BAD_REQUEST);
+    }
+}
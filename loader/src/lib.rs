@@ -15,10 +15,51 @@
 //! [responder::Responder::respond_flatten] method makes a [http] service - a
 //! function taking [http::Request] parts (method, path, headers) and returning
 //! [http::Response].
+//! [responder::Responder::respond_request_flatten] does the same taking
+//! [http::Request] directly, handling percent-encoded paths and query string
+//! (see [responder::QueryPolicy]).
+//! `OPTIONS` requests are answered with `allow` header. With
+//! [cors::CorsPolicy] set in [responder::ResponderOptions], `access-control-*`
+//! headers are added to responses and CORS preflight requests are answered
+//! (eg. for fonts loaded by other subdomains).
 //!
 //! To make a [responder::Responder], a [common::pack::Pack] is needed. It can
 //! be obtained by [loader::load] function by passing (possibly included in
 //! binary) contents of a `pack` created with the packer.
+//! If `pack` comes from less reliable location (eg. is read from fs in
+//! runtime), [loader::load_checked] can be used instead, validating the
+//! content (and optionally its checksum, compressed contents and `ETag`s)
+//! before use and not requiring `unsafe`. With `signature` feature enabled,
+//! `loader::load_signed` additionally verifies that `pack` was signed by one of
+//! trusted keys (eg. for `pack`s distributed as over-the-air updates).
+//!
+//! To keep a `pack` loaded in runtime together with its bytes, use
+//! [pack_owned::PackOwned]. [pack_owned::PackOwned::read_file] reads a `pack`
+//! file into an aligned buffer and validates it, without requiring `unsafe`
+//! code. With `mmap` feature enabled, `pack_owned::PackOwned::map_file` maps
+//! the file into memory instead, which is `unsafe`, as the file must not be
+//! modified in place while mapped. Owned `pack`s can be replaced in runtime
+//! (eg. on UI update) with [pack_swappable::PackSwappable].
+//!
+//! Responses made by [responder::Responder] borrow the `pack`. For servers
+//! requiring `'static` response bodies, [responder::ResponderOwned] keeps
+//! the `pack` in an `Arc` and returns bodies holding a reference to it, without
+//! copying file contents.
+//!
+//! With `tower` feature enabled, `tower::ResponderService` is a
+//! `tower::Service` serving `pack`, usable with axum, tonic or hyper-util
+//! without hand written glue. `tower::ResponderLayer` puts `pack` in front of
+//! inner service (eg. api routes), passing requests not found in `pack`
+//! through.
+//!
+//! With `axum` feature enabled, `axum::router` creates axum `Router` serving
+//! `pack` as a fallback, so api routes can be added on top of it. Responder
+//! errors and bodies implement `IntoResponse`. See
+//! `tests/examples/vcard_personal_portfolio_server_axum.rs` for full example.
+//!
+//! With `actix` feature enabled, `actix::scope` and `actix::default_service`
+//! serve `pack` in actix-web applications. Responder responses are converted
+//! into actix `HttpResponse` without copying content.
 //!
 //! # Examples
 //!
@@ -54,6 +95,42 @@
 //! }
 //! ```
 //!
+//! ## Serving with built-in hyper server
+//! With `hyper-server` feature enabled, `server::serve` runs a HTTP/1 and
+//! HTTP/2 server with graceful shutdown and connection limit, so no glue code
+//! is needed.
+//!
+//! ```ignore
+//! use anyhow::Error;
+//! use std::{net::{Ipv4Addr, SocketAddr}, sync::Arc};
+//! use web_static_pack::{
+//!     loader::LoadCheckedOptions,
+//!     pack_owned::PackOwned,
+//!     responder::ResponderOwned,
+//!     server::{serve, ServeOptions},
+//! };
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Error> {
+//!     // read `pack` in runtime
+//!     let pack = PackOwned::read_file(
+//!         "vcard-personal-portfolio.pack".as_ref(),
+//!         &LoadCheckedOptions::default(),
+//!     )?;
+//!
+//!     // serve it until ctrl+c is pressed
+//!     serve(
+//!         SocketAddr::from((Ipv4Addr::LOCALHOST, 8080)),
+//!         ResponderOwned::new(Arc::new(pack)),
+//!         async { tokio::signal::ctrl_c().await.unwrap() },
+//!         &ServeOptions::default(),
+//!     )
+//!     .await?;
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
 //! ## Adapting to hyper service
 //! This example is based on
 //! <https://hyper.rs/guides/1/server/graceful-shutdown/>
@@ -107,10 +184,26 @@
 
 pub use web_static_pack_common as common;
 
+#[cfg(feature = "actix")]
+pub mod actix;
+#[cfg(feature = "axum")]
+pub mod axum;
 pub mod body;
 pub mod cache_control;
 pub mod content_encoding;
+pub mod cors;
 pub mod file;
+pub mod host_router;
 pub mod loader;
 pub mod pack;
+pub mod pack_layered;
+pub mod pack_owned;
+pub mod pack_swappable;
 pub mod responder;
+#[cfg(feature = "hyper-server")]
+pub mod server;
+#[cfg(feature = "tower")]
+pub mod tower;
+
+#[cfg(test)]
+mod test_fixture;
@@ -1,9 +1,19 @@
-//! Module containing [load] function used to convert (map) serialized `pack`
-//! into [PackArchived] object.
+//! Module containing [load] and [load_checked] functions used to convert (map)
+//! serialized `pack` into [PackArchived] object.
 
-use crate::common::{PACK_FILE_MAGIC, PACK_FILE_VERSION, pack::PackArchived};
-use anyhow::{Error, ensure};
-use rkyv::access_unchecked;
+use crate::common::{
+    PACK_FILE_CHECKSUM_ALGORITHM_BLAKE3, PACK_FILE_CHECKSUM_ALGORITHM_NONE,
+    PACK_FILE_HEADER_LENGTH, PACK_FILE_MAGIC, PACK_FILE_SIGNATURE_MAGIC,
+    PACK_FILE_SIGNATURE_TRAILER_LENGTH, PACK_FILE_VERSION, file::etag_from_content,
+    pack::PackArchived,
+};
+use anyhow::Error;
+#[cfg(feature = "signature")]
+use ed25519_dalek::{Signature, VerifyingKey};
+use flate2::read::GzDecoder;
+use http::HeaderValue;
+use rkyv::{access, access_unchecked, rancor};
+use std::{fmt, io::Read};
 
 /// Alignment value for `serialized` in [load].
 pub const ALIGN_BYTES: usize = 16;
@@ -55,31 +65,819 @@ pub const ALIGN_BYTES: usize = 16;
 /// packer. Underlying loader (rkyv) relies on correct file content. If invalid
 /// content is provided it is going to cause undefined behavior.
 pub unsafe fn load(serialized: &[u8]) -> Result<&PackArchived, Error> {
-    ensure!(
-        (serialized.as_ptr() as usize).is_multiple_of(ALIGN_BYTES),
-        "invalid alignment, serialized must be aligned to {ALIGN_BYTES} bytes"
-    );
-    ensure!(serialized.len() > 16, "premature file end");
+    let Parsed { payload, .. } = parse(serialized)?;
+
+    // deserialize content
+    // NOTE: value passed to [access_unchecked] must be 16-aligned
+    let pack = unsafe { access_unchecked::<PackArchived>(payload) };
+
+    Ok(pack)
+}
+
+/// Error returned by [load_checked].
+#[derive(Debug)]
+pub enum LoadError {
+    /// `serialized` is not aligned to [ALIGN_BYTES].
+    AlignmentInvalid,
+    /// `serialized` is too short to contain a `pack`.
+    PrematureEnd,
+    /// File magic does not match [PACK_FILE_MAGIC], content is probably not a
+    /// `pack`.
+    MagicMismatch,
+    /// File version does not match [PACK_FILE_VERSION], `pack` was probably
+    /// created with different version of packer.
+    VersionMismatch {
+        /// Version found in `serialized`.
+        version: u64,
+    },
+    /// Payload length stored in header does not match actual length, `pack`
+    /// is probably truncated.
+    PayloadLengthMismatch {
+        /// Payload length stored in header.
+        expected: u64,
+        /// Actual payload length.
+        actual: usize,
+    },
+    /// Checksum algorithm stored in header is not known.
+    ChecksumAlgorithmUnknown {
+        /// Checksum algorithm found in header.
+        checksum_algorithm: u64,
+    },
+    /// Checksum is required, but `pack` was stored without one.
+    ChecksumMissing,
+    /// Payload checksum does not match the one stored in header, `pack` is
+    /// corrupted.
+    ChecksumMismatch,
+    /// Content following the payload is not a valid signature trailer.
+    TrailerInvalid,
+    /// Signature is required, but `pack` was not signed.
+    SignatureMissing,
+    /// `pack` was signed with a key not present in trusted keys.
+    SignatureKeyUntrusted,
+    /// Signature verification failed, `pack` was modified after signing.
+    SignatureInvalid,
+    /// Archive validation failed, `pack` is probably truncated or corrupted.
+    ArchiveInvalid(rancor::Error),
+    /// File contains value that cannot be used as http header.
+    HeaderValueInvalid {
+        /// Path of the file inside `pack`.
+        path: String,
+        /// Name of the header.
+        name: &'static str,
+    },
+    /// File `gzip` content cannot be decompressed.
+    ContentGzipInvalid {
+        /// Path of the file inside `pack`.
+        path: String,
+    },
+    /// File `gzip` content decompresses to something different than
+    /// `identity` content.
+    ContentGzipMismatch {
+        /// Path of the file inside `pack`.
+        path: String,
+    },
+    /// File `brotli` content cannot be decompressed.
+    ContentBrotliInvalid {
+        /// Path of the file inside `pack`.
+        path: String,
+    },
+    /// File `brotli` content decompresses to something different than
+    /// `identity` content.
+    ContentBrotliMismatch {
+        /// Path of the file inside `pack`.
+        path: String,
+    },
+    /// File `ETag` does not match the one calculated from content.
+    EtagMismatch {
+        /// Path of the file inside `pack`.
+        path: String,
+    },
+}
+impl fmt::Display for LoadError {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            LoadError::AlignmentInvalid => write!(
+                f,
+                "invalid alignment, serialized must be aligned to {ALIGN_BYTES} bytes"
+            ),
+            LoadError::PrematureEnd => write!(f, "premature file end"),
+            LoadError::MagicMismatch => {
+                write!(f, "file magic mismatch, probably not a pack file")
+            }
+            LoadError::VersionMismatch { version } => write!(
+                f,
+                "file version mismatch (got {version}, expected: {PACK_FILE_VERSION}) (probably pack created with different version)"
+            ),
+            LoadError::PayloadLengthMismatch { expected, actual } => write!(
+                f,
+                "payload length mismatch (got {actual}, expected: {expected}) (probably pack is truncated)"
+            ),
+            LoadError::ChecksumAlgorithmUnknown { checksum_algorithm } => {
+                write!(f, "unknown checksum algorithm {checksum_algorithm}")
+            }
+            LoadError::ChecksumMissing => write!(f, "pack was stored without checksum"),
+            LoadError::ChecksumMismatch => {
+                write!(f, "checksum mismatch, pack is probably corrupted")
+            }
+            LoadError::TrailerInvalid => write!(f, "invalid content after payload"),
+            LoadError::SignatureMissing => write!(f, "pack is not signed"),
+            LoadError::SignatureKeyUntrusted => {
+                write!(f, "pack is signed with untrusted key")
+            }
+            LoadError::SignatureInvalid => {
+                write!(f, "signature verification failed, pack was modified")
+            }
+            LoadError::ArchiveInvalid(_) => {
+                write!(f, "archive validation failed, pack is probably corrupted")
+            }
+            LoadError::HeaderValueInvalid { path, name } => {
+                write!(f, "file {path} contains invalid {name} header value")
+            }
+            LoadError::ContentGzipInvalid { path } => {
+                write!(f, "file {path} gzip content cannot be decompressed")
+            }
+            LoadError::ContentGzipMismatch { path } => {
+                write!(
+                    f,
+                    "file {path} gzip content does not match identity content"
+                )
+            }
+            LoadError::ContentBrotliInvalid { path } => {
+                write!(f, "file {path} br content cannot be decompressed")
+            }
+            LoadError::ContentBrotliMismatch { path } => {
+                write!(f, "file {path} br content does not match identity content")
+            }
+            LoadError::EtagMismatch { path } => {
+                write!(f, "file {path} etag does not match content")
+            }
+        }
+    }
+}
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::ArchiveInvalid(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Settings for [load_checked] function.
+///
+/// If not sure what to set here, use [Default].
+#[derive(Debug)]
+pub struct LoadCheckedOptions {
+    /// Verify payload checksum stored in header (if `pack` was stored with
+    /// one). Enabled by default.
+    ///
+    /// Calculating checksum requires reading the whole `pack`, which for
+    /// mmap'd files means loading all of it into memory. If not set, only
+    /// payload length is checked, which is enough to detect truncated files.
+    pub verify_checksum: bool,
+    /// Fail with [LoadError::ChecksumMissing] if `pack` was stored without
+    /// checksum.
+    pub checksum_required: bool,
+    /// Verify that `gzip` and `brotli` content of every file decompresses to
+    /// its `identity` content (see [content_gzip_check] and
+    /// [content_brotli_check]).
+    ///
+    /// This decompresses all files, so it's much slower than checksum
+    /// verification. Useful mostly for auditing `pack`s, not for loading them
+    /// on every start.
+    pub verify_content_compressed: bool,
+    /// Verify that `ETag` of every file matches the one calculated from its
+    /// content.
+    ///
+    /// This hashes content of all files, similarly to
+    /// [Self::verify_content_compressed] it's intended mostly for auditing.
+    pub verify_etag: bool,
+}
+impl Default for LoadCheckedOptions {
+    fn default() -> Self {
+        Self {
+            verify_checksum: true,
+            checksum_required: false,
+            verify_content_compressed: false,
+            verify_etag: false,
+        }
+    }
+}
+
+/// Safe version of [load], validating the whole `serialized` content before
+/// returning [PackArchived].
+///
+/// In addition to checks made by [load], this validates archive structure
+/// (with [rkyv] bytecheck) and checks if all header values stored in `pack`
+/// are valid http header values. If requested in `options`, it also verifies
+/// payload checksum, compressed file contents and `ETag`s. Truncated or
+/// corrupted `pack`s are reported as [LoadError] instead of causing undefined
+/// behavior.
+///
+/// Validation requires going through the whole `pack`, so it's slower than
+/// [load]. Prefer this function when loading `pack`s from untrusted or
+/// unreliable locations, eg. from fs in runtime.
+///
+/// # Examples
+///
+/// ```ignore
+/// let pack_archived_serialized = std::fs::read(...).unwrap(); // must be aligned
+/// let pack = web_static_pack::loader::load_checked(
+///     &pack_archived_serialized,
+///     &LoadCheckedOptions::default(),
+/// )
+/// .unwrap();
+/// ```
+pub fn load_checked<'s>(
+    serialized: &'s [u8],
+    options: &LoadCheckedOptions,
+) -> Result<&'s PackArchived, LoadError> {
+    let Parsed {
+        header, payload, ..
+    } = parse(serialized)?;
+
+    if options.checksum_required && header.checksum_algorithm == PACK_FILE_CHECKSUM_ALGORITHM_NONE {
+        return Err(LoadError::ChecksumMissing);
+    }
+    if options.verify_checksum
+        && header.checksum_algorithm == PACK_FILE_CHECKSUM_ALGORITHM_BLAKE3
+        && *blake3::hash(payload).as_bytes() != header.checksum
+    {
+        return Err(LoadError::ChecksumMismatch);
+    }
+
+    let pack = access::<PackArchived, rancor::Error>(payload).map_err(LoadError::ArchiveInvalid)?;
+
+    for (pack_path, file) in pack.files_by_path.iter() {
+        let header_value_invalid = |name| LoadError::HeaderValueInvalid {
+            path: pack_path.to_string(),
+            name,
+        };
+
+        HeaderValue::from_str(&file.content_type)
+            .map_err(|_| header_value_invalid("content-type"))?;
+        HeaderValue::from_str(&file.etag).map_err(|_| header_value_invalid("etag"))?;
+
+        if options.verify_content_compressed {
+            if let Some(content_gzip) = file.content_gzip.as_ref() {
+                content_gzip_check(&file.content, content_gzip).map_err(|error| match error {
+                    ContentCompressedError::Invalid => LoadError::ContentGzipInvalid {
+                        path: pack_path.to_string(),
+                    },
+                    ContentCompressedError::Mismatch => LoadError::ContentGzipMismatch {
+                        path: pack_path.to_string(),
+                    },
+                })?;
+            }
+            if let Some(content_brotli) = file.content_brotli.as_ref() {
+                content_brotli_check(&file.content, content_brotli).map_err(
+                    |error| match error {
+                        ContentCompressedError::Invalid => LoadError::ContentBrotliInvalid {
+                            path: pack_path.to_string(),
+                        },
+                        ContentCompressedError::Mismatch => LoadError::ContentBrotliMismatch {
+                            path: pack_path.to_string(),
+                        },
+                    },
+                )?;
+            }
+        }
+        if options.verify_etag && etag_from_content(&file.content) != *file.etag {
+            return Err(LoadError::EtagMismatch {
+                path: pack_path.to_string(),
+            });
+        }
+    }
+
+    Ok(pack)
+}
+
+/// Error returned by [content_gzip_check] and [content_brotli_check].
+#[derive(PartialEq, Eq, Debug)]
+pub enum ContentCompressedError {
+    /// Compressed content cannot be decompressed.
+    Invalid,
+    /// Compressed content decompresses to something different than `identity`
+    /// content.
+    Mismatch,
+}
+
+/// Checks if `content_gzip` decompresses to `content`.
+pub fn content_gzip_check(
+    content: &[u8],
+    content_gzip: &[u8],
+) -> Result<(), ContentCompressedError> {
+    let mut content_decompressed = Vec::new();
+    GzDecoder::new(content_gzip)
+        .read_to_end(&mut content_decompressed)
+        .map_err(|_| ContentCompressedError::Invalid)?;
+
+    if *content_decompressed != *content {
+        return Err(ContentCompressedError::Mismatch);
+    }
+
+    Ok(())
+}
+
+/// Checks if `content_brotli` decompresses to `content`.
+pub fn content_brotli_check(
+    content: &[u8],
+    content_brotli: &[u8],
+) -> Result<(), ContentCompressedError> {
+    let mut content_decompressed = Vec::new();
+    brotli_decompressor::BrotliDecompress(&mut &*content_brotli, &mut content_decompressed)
+        .map_err(|_| ContentCompressedError::Invalid)?;
+
+    if *content_decompressed != *content {
+        return Err(ContentCompressedError::Mismatch);
+    }
+
+    Ok(())
+}
+
+/// Verifies `pack` signature against `trusted_keys`, then loads it like
+/// [load_checked].
+///
+/// `pack` must be signed by packer (see `sign` subcommand) with a key
+/// matching one of `trusted_keys`. This allows eg. accepting `pack`s
+/// distributed as over-the-air updates only if they come from a trusted build
+/// system.
+///
+/// Signature covers BLAKE3 hash of the header and the payload, so `options`
+/// checksum settings only affect checking for unintentional corruption.
+///
+/// # Examples
+///
+/// ```ignore
+/// let trusted_key = ed25519_dalek::VerifyingKey::from_bytes(&[...]).unwrap();
+///
+/// let pack_archived_serialized = std::fs::read(...).unwrap(); // must be aligned
+/// let pack = web_static_pack::loader::load_signed(
+///     &pack_archived_serialized,
+///     &[trusted_key],
+///     &LoadCheckedOptions::default(),
+/// )
+/// .unwrap();
+/// ```
+#[cfg(feature = "signature")]
+pub fn load_signed<'s>(
+    serialized: &'s [u8],
+    trusted_keys: &[VerifyingKey],
+    options: &LoadCheckedOptions,
+) -> Result<&'s PackArchived, LoadError> {
+    let Parsed {
+        header_payload,
+        trailer,
+        ..
+    } = parse(serialized)?;
+
+    let trailer = trailer.ok_or(LoadError::SignatureMissing)?;
+
+    let verifying_key = trusted_keys
+        .iter()
+        .find(|trusted_key| *trusted_key.as_bytes() == trailer.public_key)
+        .ok_or(LoadError::SignatureKeyUntrusted)?;
+    verifying_key
+        .verify_strict(
+            blake3::hash(header_payload).as_bytes(),
+            &Signature::from_bytes(&trailer.signature),
+        )
+        .map_err(|_| LoadError::SignatureInvalid)?;
+
+    load_checked(serialized, options)
+}
+
+/// Parsed `pack` header, see [PACK_FILE_HEADER_LENGTH] for layout.
+#[derive(Debug)]
+struct Header {
+    checksum_algorithm: u64,
+    checksum: [u8; 32],
+}
+
+/// Parsed signature trailer, see [PACK_FILE_SIGNATURE_TRAILER_LENGTH] for
+/// layout.
+#[derive(Debug)]
+#[cfg_attr(not(feature = "signature"), allow(dead_code))]
+struct Trailer {
+    public_key: [u8; 32],
+    signature: [u8; 64],
+}
+
+/// `serialized` split into parts.
+#[derive(Debug)]
+#[cfg_attr(not(feature = "signature"), allow(dead_code))]
+struct Parsed<'s> {
+    header: Header,
+    /// Header and payload, the part covered by signature (by its hash).
+    header_payload: &'s [u8],
+    payload: &'s [u8],
+    trailer: Option<Trailer>,
+}
+
+/// Checks alignment, magic, version and payload length of `serialized` and
+/// splits it into parts.
+fn parse(serialized: &[u8]) -> Result<Parsed<'_>, LoadError> {
+    if !(serialized.as_ptr() as usize).is_multiple_of(ALIGN_BYTES) {
+        return Err(LoadError::AlignmentInvalid);
+    }
+    if serialized.len() < PACK_FILE_HEADER_LENGTH {
+        return Err(LoadError::PrematureEnd);
+    }
+
+    let u64_at = |offset: usize| {
+        let bytes: [u8; 8] = serialized[offset..offset + 8].try_into().unwrap();
+        u64::from_ne_bytes(bytes)
+    };
 
     // check file magic
-    let file_magic_bytes: [u8; 8] = serialized[0..8].try_into()?;
-    let file_magic = u64::from_ne_bytes(file_magic_bytes);
-    ensure!(
-        file_magic == PACK_FILE_MAGIC,
-        "file magic mismatch, probably not a pack file"
-    );
+    let file_magic = u64_at(0);
+    if file_magic != PACK_FILE_MAGIC {
+        return Err(LoadError::MagicMismatch);
+    }
 
     // check file version
-    let file_version_bytes: [u8; 8] = serialized[8..16].try_into()?;
-    let file_version = u64::from_ne_bytes(file_version_bytes);
-    ensure!(
-        file_version == PACK_FILE_VERSION,
-        "file version mismatch (got {file_version}, expected: {PACK_FILE_VERSION}) (probably pack created with different version)"
-    );
+    let file_version = u64_at(8);
+    if file_version != PACK_FILE_VERSION {
+        return Err(LoadError::VersionMismatch {
+            version: file_version,
+        });
+    }
 
-    // deserialize content
-    // NOTE: value passed to [access_unchecked] must be 16-aligned
-    let pack = unsafe { access_unchecked::<PackArchived>(&serialized[16..]) };
+    // check checksum algorithm
+    let checksum_algorithm = u64_at(16);
+    if ![
+        PACK_FILE_CHECKSUM_ALGORITHM_NONE,
+        PACK_FILE_CHECKSUM_ALGORITHM_BLAKE3,
+    ]
+    .contains(&checksum_algorithm)
+    {
+        return Err(LoadError::ChecksumAlgorithmUnknown { checksum_algorithm });
+    }
 
-    Ok(pack)
+    // check payload length, this is cheap way to detect truncated files
+    let payload_length = u64_at(24);
+    let payload_length_available = serialized.len() - PACK_FILE_HEADER_LENGTH;
+    if payload_length > payload_length_available as u64 {
+        return Err(LoadError::PayloadLengthMismatch {
+            expected: payload_length,
+            actual: payload_length_available,
+        });
+    }
+    let payload_end = PACK_FILE_HEADER_LENGTH + payload_length as usize;
+
+    let checksum: [u8; 32] = serialized[32..64].try_into().unwrap();
+
+    // anything after payload must be a signature trailer
+    let trailer = &serialized[payload_end..];
+    let trailer = if !trailer.is_empty() {
+        if trailer.len() != PACK_FILE_SIGNATURE_TRAILER_LENGTH
+            || u64_at(payload_end) != PACK_FILE_SIGNATURE_MAGIC
+        {
+            return Err(LoadError::TrailerInvalid);
+        }
+
+        Some(Trailer {
+            public_key: trailer[8..40].try_into().unwrap(),
+            signature: trailer[40..104].try_into().unwrap(),
+        })
+    } else {
+        None
+    };
+
+    Ok(Parsed {
+        header: Header {
+            checksum_algorithm,
+            checksum,
+        },
+        header_payload: &serialized[..payload_end],
+        payload: &serialized[PACK_FILE_HEADER_LENGTH..payload_end],
+        trailer,
+    })
+}
+
+#[cfg(test)]
+mod test_loader {
+    use super::{LoadCheckedOptions, LoadError, load, load_checked};
+    use crate::common::{
+        PACK_FILE_CHECKSUM_ALGORITHM_BLAKE3, PACK_FILE_CHECKSUM_ALGORITHM_NONE,
+        PACK_FILE_HEADER_LENGTH, PACK_FILE_MAGIC, PACK_FILE_VERSION,
+        cache_control::CacheControl,
+        file::{File, etag_from_content},
+        pack::Pack,
+        pack_path::PackPath,
+    };
+    use flate2::{Compression, write::GzEncoder};
+    use rkyv::{rancor, util::AlignedVec};
+    use std::{collections::HashMap, io::Write};
+
+    fn file(content_type: &str) -> File {
+        File {
+            content: Box::new(*b"content"),
+            content_gzip: None,
+            content_brotli: None,
+            content_type: content_type.to_owned(),
+            etag: "\"etag\"".to_owned(),
+            cache_control: CacheControl::MaxCache,
+        }
+    }
+    fn content_gzip(content: &[u8]) -> Box<[u8]> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content).unwrap();
+        encoder.finish().unwrap().into_boxed_slice()
+    }
+
+    // builds valid serialized pack, with blake3 checksum
+    fn serialized(content_type: &str) -> AlignedVec {
+        serialized_file(file(content_type))
+    }
+    fn serialized_file(file: File) -> AlignedVec {
+        let pack = Pack {
+            files_by_path: HashMap::from([(PackPath::from_string("/index.html".to_owned()), file)]),
+        };
+        let payload = rkyv::to_bytes::<rancor::Error>(&pack).unwrap();
+
+        let mut serialized = AlignedVec::new();
+        serialized.extend_from_slice(&PACK_FILE_MAGIC.to_ne_bytes());
+        serialized.extend_from_slice(&PACK_FILE_VERSION.to_ne_bytes());
+        serialized.extend_from_slice(&PACK_FILE_CHECKSUM_ALGORITHM_BLAKE3.to_ne_bytes());
+        serialized.extend_from_slice(&(payload.len() as u64).to_ne_bytes());
+        serialized.extend_from_slice(blake3::hash(&payload).as_bytes());
+        serialized.extend_from_slice(&payload);
+        serialized
+    }
+    fn header_u64_set(
+        serialized: &mut AlignedVec,
+        offset: usize,
+        value: u64,
+    ) {
+        serialized[offset..offset + 8].copy_from_slice(&value.to_ne_bytes());
+    }
+
+    static OPTIONS_VERIFY_CHECKSUM: LoadCheckedOptions = LoadCheckedOptions {
+        verify_checksum: true,
+        checksum_required: true,
+        verify_content_compressed: false,
+        verify_etag: false,
+    };
+
+    #[test]
+    fn load_checked_loads_valid() {
+        let serialized = serialized("text/html");
+
+        let pack = load_checked(&serialized, &OPTIONS_VERIFY_CHECKSUM).unwrap();
+        assert_eq!(
+            &*pack.files_by_path.get("/index.html").unwrap().content,
+            b"content"
+        );
+    }
+
+    #[test]
+    fn load_checked_fails_for_invalid_header() {
+        let serialized = serialized("text/html");
+
+        assert!(matches!(
+            load_checked(&serialized[..8], &LoadCheckedOptions::default()),
+            Err(LoadError::PrematureEnd)
+        ));
+        assert!(matches!(
+            load_checked(&serialized[1..], &LoadCheckedOptions::default()),
+            Err(LoadError::AlignmentInvalid)
+        ));
+
+        let mut serialized_magic_invalid = serialized.clone();
+        header_u64_set(&mut serialized_magic_invalid, 0, 0);
+        assert!(matches!(
+            load_checked(&serialized_magic_invalid, &LoadCheckedOptions::default()),
+            Err(LoadError::MagicMismatch)
+        ));
+
+        let mut serialized_version_invalid = serialized.clone();
+        header_u64_set(&mut serialized_version_invalid, 8, 1);
+        assert!(matches!(
+            load_checked(&serialized_version_invalid, &LoadCheckedOptions::default()),
+            Err(LoadError::VersionMismatch { version: 1 })
+        ));
+
+        let mut serialized_checksum_algorithm_invalid = serialized.clone();
+        header_u64_set(&mut serialized_checksum_algorithm_invalid, 16, 1234);
+        assert!(matches!(
+            load_checked(
+                &serialized_checksum_algorithm_invalid,
+                &LoadCheckedOptions::default()
+            ),
+            Err(LoadError::ChecksumAlgorithmUnknown {
+                checksum_algorithm: 1234
+            })
+        ));
+    }
+
+    #[test]
+    fn load_fails_for_truncated_payload() {
+        let serialized = serialized("text/html");
+        let serialized = &serialized[..serialized.len() - 16];
+
+        assert!(unsafe { load(serialized) }.is_err());
+        assert!(matches!(
+            load_checked(serialized, &LoadCheckedOptions::default()),
+            Err(LoadError::PayloadLengthMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn load_checked_fails_for_invalid_trailer() {
+        let mut serialized = serialized("text/html");
+        serialized.extend_from_slice(b"garbage");
+
+        assert!(matches!(
+            load_checked(&serialized, &LoadCheckedOptions::default()),
+            Err(LoadError::TrailerInvalid)
+        ));
+    }
+
+    #[test]
+    fn load_checked_fails_for_invalid_archive() {
+        let serialized_valid = serialized("text/html");
+        // truncate payload, but keep header consistent
+        let mut serialized = AlignedVec::<16>::new();
+        serialized.extend_from_slice(&serialized_valid[..serialized_valid.len() - 16]);
+        let payload_length = (serialized.len() - PACK_FILE_HEADER_LENGTH) as u64;
+        header_u64_set(&mut serialized, 24, payload_length);
+        let checksum = blake3::hash(&serialized[PACK_FILE_HEADER_LENGTH..]);
+        serialized[32..64].copy_from_slice(checksum.as_bytes());
+
+        assert!(matches!(
+            load_checked(&serialized, &LoadCheckedOptions::default()),
+            Err(LoadError::ArchiveInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn load_checked_verifies_checksum() {
+        let mut serialized = serialized("text/html");
+        let serialized_length = serialized.len();
+        serialized[serialized_length - 1] ^= 0xff;
+
+        assert!(matches!(
+            load_checked(&serialized, &OPTIONS_VERIFY_CHECKSUM),
+            Err(LoadError::ChecksumMismatch)
+        ));
+
+        header_u64_set(&mut serialized, 16, PACK_FILE_CHECKSUM_ALGORITHM_NONE);
+        assert!(matches!(
+            load_checked(&serialized, &OPTIONS_VERIFY_CHECKSUM),
+            Err(LoadError::ChecksumMissing)
+        ));
+    }
+
+    #[test]
+    fn load_checked_fails_for_invalid_header_value() {
+        let serialized = serialized("text/html\n");
+
+        assert!(matches!(
+            load_checked(&serialized, &LoadCheckedOptions::default()),
+            Err(LoadError::HeaderValueInvalid {
+                name: "content-type",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn load_checked_verifies_content_compressed() {
+        let options = LoadCheckedOptions {
+            verify_content_compressed: true,
+            ..LoadCheckedOptions::default()
+        };
+
+        let serialized_gzip_valid = serialized_file(File {
+            content_gzip: Some(content_gzip(b"content")),
+            ..file("text/html")
+        });
+        assert!(load_checked(&serialized_gzip_valid, &options).is_ok());
+
+        let serialized_gzip_invalid = serialized_file(File {
+            content_gzip: Some(Box::new(*b"garbage")),
+            ..file("text/html")
+        });
+        // not checked unless requested
+        assert!(load_checked(&serialized_gzip_invalid, &LoadCheckedOptions::default()).is_ok());
+        assert!(matches!(
+            load_checked(&serialized_gzip_invalid, &options),
+            Err(LoadError::ContentGzipInvalid { .. })
+        ));
+
+        let serialized_gzip_mismatch = serialized_file(File {
+            content_gzip: Some(content_gzip(b"other content")),
+            ..file("text/html")
+        });
+        assert!(matches!(
+            load_checked(&serialized_gzip_mismatch, &options),
+            Err(LoadError::ContentGzipMismatch { .. })
+        ));
+
+        let serialized_brotli_invalid = serialized_file(File {
+            content_brotli: Some(Box::new(*b"garbage")),
+            ..file("text/html")
+        });
+        assert!(matches!(
+            load_checked(&serialized_brotli_invalid, &options),
+            Err(LoadError::ContentBrotliInvalid { .. })
+        ));
+    }
+
+    #[test]
+    fn load_checked_verifies_etag() {
+        let options = LoadCheckedOptions {
+            verify_etag: true,
+            ..LoadCheckedOptions::default()
+        };
+
+        assert!(matches!(
+            load_checked(&serialized("text/html"), &options),
+            Err(LoadError::EtagMismatch { .. })
+        ));
+        assert!(
+            load_checked(
+                &serialized_file(File {
+                    etag: etag_from_content(b"content"),
+                    ..file("text/html")
+                }),
+                &options
+            )
+            .is_ok()
+        );
+    }
+
+    #[cfg(feature = "signature")]
+    mod signature {
+        use super::{super::load_signed, LoadCheckedOptions, LoadError, serialized};
+        use crate::common::{PACK_FILE_HEADER_LENGTH, PACK_FILE_SIGNATURE_MAGIC};
+        use ed25519_dalek::{Signer, SigningKey};
+        use rkyv::util::AlignedVec;
+
+        fn serialized_signed(signing_key: &SigningKey) -> AlignedVec {
+            let mut serialized = serialized("text/html");
+            let signature = signing_key.sign(blake3::hash(&serialized).as_bytes());
+            serialized.extend_from_slice(&PACK_FILE_SIGNATURE_MAGIC.to_ne_bytes());
+            serialized.extend_from_slice(signing_key.verifying_key().as_bytes());
+            serialized.extend_from_slice(&signature.to_bytes());
+            serialized
+        }
+
+        #[test]
+        fn load_signed_returns_expected() {
+            let signing_key = SigningKey::from_bytes(&[1; 32]);
+            let signing_key_other = SigningKey::from_bytes(&[2; 32]);
+
+            let serialized_signed = serialized_signed(&signing_key);
+
+            // valid
+            assert!(
+                load_signed(
+                    &serialized_signed,
+                    &[
+                        signing_key_other.verifying_key(),
+                        signing_key.verifying_key()
+                    ],
+                    &LoadCheckedOptions::default(),
+                )
+                .is_ok()
+            );
+
+            // not signed by trusted key
+            assert!(matches!(
+                load_signed(
+                    &serialized_signed,
+                    &[signing_key_other.verifying_key()],
+                    &LoadCheckedOptions::default(),
+                ),
+                Err(LoadError::SignatureKeyUntrusted)
+            ));
+
+            // modified
+            let mut serialized_modified = serialized_signed.clone();
+            serialized_modified[PACK_FILE_HEADER_LENGTH] ^= 0xff;
+            assert!(matches!(
+                load_signed(
+                    &serialized_modified,
+                    &[signing_key.verifying_key()],
+                    &LoadCheckedOptions::default(),
+                ),
+                Err(LoadError::SignatureInvalid)
+            ));
+
+            // not signed
+            assert!(matches!(
+                load_signed(
+                    &serialized("text/html"),
+                    &[signing_key.verifying_key()],
+                    &LoadCheckedOptions::default(),
+                ),
+                Err(LoadError::SignatureMissing)
+            ));
+        }
+    }
 }
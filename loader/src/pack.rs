@@ -1,4 +1,4 @@
-//! Pack related types. Provides [Pack] trait.
+//! Pack related types. Provides [Pack] and [PackEnumerate] traits.
 
 use crate::{
     common::{
@@ -26,6 +26,20 @@ pub trait Pack {
         path: &str,
     ) -> Option<&Self::File>;
 }
+// allows eg. `&'static PackArchived` to be used where owned `pack` is needed
+impl<P> Pack for &P
+where
+    P: Pack + ?Sized,
+{
+    type File = P::File;
+
+    fn get_file_by_path(
+        &self,
+        path: &str,
+    ) -> Option<&Self::File> {
+        (**self).get_file_by_path(path)
+    }
+}
 impl Pack for Pack_ {
     type File = File_;
 
@@ -48,3 +62,218 @@ impl Pack for PackArchived {
         Some(file)
     }
 }
+
+/// Extension of [Pack] allowing enumeration of its contents (eg. for sitemaps,
+/// service worker precache lists or health checks).
+///
+/// Kept separate from [Pack], so implementations serving only lookups (eg.
+/// proxying to other storage) don't have to provide it. Only
+/// [PackEnumerate::files] needs to be provided by implementations, other
+/// methods have default implementations built on top of it, which may be
+/// overridden with more efficient ones.
+pub trait PackEnumerate: Pack {
+    /// Iterates over all files in this `pack`, together with their paths.
+    /// Iteration order is implementation specific.
+    fn files(&self) -> impl Iterator<Item = (&str, &Self::File)>;
+
+    /// Iterates over paths of all files in this `pack`, in order of
+    /// [PackEnumerate::files].
+    fn paths(&self) -> impl Iterator<Item = &str> {
+        self.files().map(|(path, _file)| path)
+    }
+
+    /// Like [PackEnumerate::files], but yields only files with path starting
+    /// with `prefix`, eg. `/assets/`.
+    fn files_by_prefix<'s>(
+        &'s self,
+        prefix: &str,
+    ) -> impl Iterator<Item = (&'s str, &'s Self::File)> {
+        self.files()
+            .filter(move |(path, _file)| path.starts_with(prefix))
+    }
+
+    /// Returns number of files in this `pack`.
+    fn len(&self) -> usize {
+        self.files().count()
+    }
+
+    /// Returns `true` if this `pack` contains no files.
+    fn is_empty(&self) -> bool {
+        self.files().next().is_none()
+    }
+}
+impl<P> PackEnumerate for &P
+where
+    P: PackEnumerate + ?Sized,
+{
+    fn files(&self) -> impl Iterator<Item = (&str, &Self::File)> {
+        (**self).files()
+    }
+
+    fn paths(&self) -> impl Iterator<Item = &str> {
+        (**self).paths()
+    }
+
+    fn files_by_prefix<'s>(
+        &'s self,
+        prefix: &str,
+    ) -> impl Iterator<Item = (&'s str, &'s Self::File)> {
+        (**self).files_by_prefix(prefix)
+    }
+
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+
+    fn is_empty(&self) -> bool {
+        (**self).is_empty()
+    }
+}
+impl PackEnumerate for Pack_ {
+    fn files(&self) -> impl Iterator<Item = (&str, &Self::File)> {
+        self.files_by_path
+            .iter()
+            .map(|(pack_path, file)| (&**pack_path, file))
+    }
+
+    fn len(&self) -> usize {
+        self.files_by_path.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.files_by_path.is_empty()
+    }
+}
+impl PackEnumerate for PackArchived {
+    fn files(&self) -> impl Iterator<Item = (&str, &Self::File)> {
+        self.files_by_path
+            .iter()
+            .map(|(pack_path, file)| (&**pack_path, file))
+    }
+
+    // files are archived sorted by path, so prefix lookup is a binary search
+    fn files_by_prefix<'s>(
+        &'s self,
+        prefix: &str,
+    ) -> impl Iterator<Item = (&'s str, &'s Self::File)> {
+        self.files_by_path
+            .iter_prefix(prefix)
+            .map(|(pack_path, file)| (&**pack_path, file))
+    }
+
+    fn len(&self) -> usize {
+        self.files_by_path.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.files_by_path.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test_pack {
+    use super::{Pack, PackEnumerate};
+    use crate::common::{
+        cache_control::CacheControl,
+        file::File,
+        pack::{Pack as Pack_, PackArchived},
+        pack_path::PackPath,
+    };
+    use rkyv::{rancor, util::AlignedVec};
+    use test_case::test_case;
+
+    static PATHS: [&str; 4] = [
+        "/assets.txt",
+        "/assets/a.css",
+        "/assets/b.js",
+        "/index.html",
+    ];
+
+    fn pack() -> Pack_ {
+        Pack_ {
+            files_by_path: PATHS
+                .iter()
+                .map(|path| {
+                    (
+                        PackPath::from_string((*path).to_owned()),
+                        File {
+                            content: path.as_bytes().into(),
+                            content_gzip: None,
+                            content_brotli: None,
+                            content_type: "text/plain".to_owned(),
+                            etag: "\"etag\"".to_owned(),
+                            cache_control: CacheControl::MaxCache,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+    fn serialized() -> AlignedVec {
+        rkyv::to_bytes::<rancor::Error>(&pack()).unwrap()
+    }
+    fn pack_archived(serialized: &AlignedVec) -> &PackArchived {
+        rkyv::access::<PackArchived, rancor::Error>(serialized).unwrap()
+    }
+
+    // collects paths sorted, as order is implementation specific
+    fn paths_sorted<'p>(paths: impl Iterator<Item = &'p str>) -> Vec<&'p str> {
+        let mut paths = paths.collect::<Vec<_>>();
+        paths.sort_unstable();
+        paths
+    }
+
+    #[test]
+    fn pack_is_dyn_compatible() {
+        let pack = pack();
+        let pack: &dyn Pack<File = File> = &pack;
+
+        assert!(pack.get_file_by_path("/index.html").is_some());
+    }
+
+    #[test]
+    fn files_returns_all_files() {
+        let pack = pack();
+        let serialized = serialized();
+        let pack_archived = pack_archived(&serialized);
+
+        assert_eq!(paths_sorted(pack.paths()), PATHS);
+        assert_eq!(pack.len(), PATHS.len());
+        assert!(!pack.is_empty());
+
+        // archived files are ordered by path
+        assert_eq!(pack_archived.paths().collect::<Vec<_>>(), PATHS);
+        assert_eq!(pack_archived.len(), PATHS.len());
+        assert!(!pack_archived.is_empty());
+
+        for (path, file) in pack_archived.files() {
+            assert_eq!(&*file.content, path.as_bytes());
+        }
+    }
+
+    #[test_case("/assets/", &["/assets/a.css", "/assets/b.js"]; "directory")]
+    #[test_case("/assets", &["/assets.txt", "/assets/a.css", "/assets/b.js"]; "partial")]
+    #[test_case("/index.html", &["/index.html"]; "exact")]
+    #[test_case("/missing", &[]; "missing")]
+    fn files_by_prefix_returns_expected(
+        prefix: &str,
+        expected: &[&str],
+    ) {
+        let pack = pack();
+        let serialized = serialized();
+        let pack_archived = pack_archived(&serialized);
+
+        assert_eq!(
+            paths_sorted(pack.files_by_prefix(prefix).map(|(path, _file)| path)),
+            expected
+        );
+        assert_eq!(
+            paths_sorted(
+                pack_archived
+                    .files_by_prefix(prefix)
+                    .map(|(path, _file)| path)
+            ),
+            expected
+        );
+    }
+}
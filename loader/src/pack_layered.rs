@@ -0,0 +1,267 @@
+//! Layered (overlay) packs. Provides [PackLayered], a [Pack] combining
+//! multiple `pack`s.
+
+use crate::pack::{Pack, PackEnumerate};
+
+/// [Pack] composed of multiple `pack`s (called layers), queried in priority
+/// order.
+///
+/// Looking for a file goes through layers starting from the first one (the
+/// highest priority) and returns file from the first layer containing requested
+/// path. This allows serving eg. a base UI `pack` with a per-customer branding
+/// `pack` overriding some of its files, without merging them at build time.
+///
+/// As [PackLayered] implements [Pack], it can be directly used with
+/// [crate::responder::Responder].
+///
+/// # Examples
+///
+/// ```ignore
+/// let pack_base = web_static_pack::loader::load(...).unwrap();
+/// let pack_branding = web_static_pack::loader::load(...).unwrap();
+///
+/// // files from `pack_branding` take precedence over `pack_base`
+/// let pack_layered = PackLayered::new(Box::new([pack_branding, pack_base]));
+///
+/// // check which layer serves the logo
+/// assert_eq!(pack_layered.get_layer_index_by_path("/logo.svg"), Some(0));
+///
+/// let responder = web_static_pack::responder::Responder::new(&pack_layered);
+/// ```
+#[derive(Debug)]
+pub struct PackLayered<'p, P>
+where
+    P: Pack,
+{
+    layers: Box<[&'p P]>,
+}
+impl<'p, P> PackLayered<'p, P>
+where
+    P: Pack,
+{
+    /// Creates new instance from list of layers. First layer has the highest
+    /// priority.
+    pub fn new(layers: Box<[&'p P]>) -> Self {
+        Self { layers }
+    }
+
+    /// Returns list of layers, in priority order.
+    pub fn layers(&self) -> &[&'p P] {
+        &self.layers
+    }
+
+    /// Like [Pack::get_file_by_path], but also returns index (in
+    /// [Self::layers]) of the layer the file was found in.
+    pub fn get_file_layer_index_by_path(
+        &self,
+        path: &str,
+    ) -> Option<(&P::File, usize)> {
+        self.layers
+            .iter()
+            .enumerate()
+            .find_map(|(layer_index, layer)| {
+                let file = layer.get_file_by_path(path)?;
+                Some((file, layer_index))
+            })
+    }
+
+    /// Returns index (in [Self::layers]) of the layer serving given path.
+    /// Returns [None] if none of the layers contains the path.
+    pub fn get_layer_index_by_path(
+        &self,
+        path: &str,
+    ) -> Option<usize> {
+        let (_file, layer_index) = self.get_file_layer_index_by_path(path)?;
+        Some(layer_index)
+    }
+}
+impl<P> Pack for PackLayered<'_, P>
+where
+    P: Pack,
+{
+    type File = P::File;
+
+    fn get_file_by_path(
+        &self,
+        path: &str,
+    ) -> Option<&Self::File> {
+        let (file, _layer_index) = self.get_file_layer_index_by_path(path)?;
+        Some(file)
+    }
+}
+impl<P> PackEnumerate for PackLayered<'_, P>
+where
+    P: PackEnumerate,
+{
+    // files shadowed by higher priority layers are skipped
+    fn files(&self) -> impl Iterator<Item = (&str, &Self::File)> {
+        self.layers
+            .iter()
+            .enumerate()
+            .flat_map(move |(layer_index, layer)| {
+                layer.files().filter(move |(path, _file)| {
+                    self.get_layer_index_by_path(path) == Some(layer_index)
+                })
+            })
+    }
+
+    fn files_by_prefix<'s>(
+        &'s self,
+        prefix: &str,
+    ) -> impl Iterator<Item = (&'s str, &'s Self::File)> {
+        self.layers
+            .iter()
+            .enumerate()
+            .flat_map(move |(layer_index, layer)| {
+                layer.files_by_prefix(prefix).filter(move |(path, _file)| {
+                    self.get_layer_index_by_path(path) == Some(layer_index)
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod test_pack_layered {
+    use super::PackLayered;
+    use crate::{
+        cache_control::CacheControl,
+        file::File,
+        pack::{Pack, PackEnumerate},
+    };
+    use http::HeaderValue;
+    use test_case::test_case;
+
+    #[derive(Debug)]
+    struct FileMock {
+        content: &'static [u8],
+    }
+    impl File for FileMock {
+        fn content(&self) -> &[u8] {
+            self.content
+        }
+        fn content_gzip(&self) -> Option<&[u8]> {
+            None
+        }
+        fn content_brotli(&self) -> Option<&[u8]> {
+            None
+        }
+
+        fn content_type(&self) -> HeaderValue {
+            unimplemented!()
+        }
+        fn etag(&self) -> HeaderValue {
+            unimplemented!()
+        }
+        fn cache_control(&self) -> CacheControl {
+            unimplemented!()
+        }
+    }
+
+    struct PackMock {
+        files: &'static [(&'static str, FileMock)],
+    }
+    impl Pack for PackMock {
+        type File = FileMock;
+
+        fn get_file_by_path(
+            &self,
+            path: &str,
+        ) -> Option<&Self::File> {
+            self.files
+                .iter()
+                .find(|(file_path, _file)| *file_path == path)
+                .map(|(_file_path, file)| file)
+        }
+    }
+    impl PackEnumerate for PackMock {
+        fn files(&self) -> impl Iterator<Item = (&str, &Self::File)> {
+            self.files
+                .iter()
+                .map(|(file_path, file)| (*file_path, file))
+        }
+    }
+
+    static PACK_OVERLAY: PackMock = PackMock {
+        files: &[(
+            "/logo.svg",
+            FileMock {
+                content: b"overlay-logo",
+            },
+        )],
+    };
+    static PACK_BASE: PackMock = PackMock {
+        files: &[
+            (
+                "/index.html",
+                FileMock {
+                    content: b"base-index",
+                },
+            ),
+            (
+                "/logo.svg",
+                FileMock {
+                    content: b"base-logo",
+                },
+            ),
+        ],
+    };
+
+    #[test_case("/logo.svg", Some((b"overlay-logo".as_slice(), 0)); "overridden by overlay")]
+    #[test_case("/index.html", Some((b"base-index".as_slice(), 1)); "falls through to base")]
+    #[test_case("/missing", None; "missing in all layers")]
+    fn get_file_layer_index_by_path_returns_expected(
+        path: &str,
+        expected: Option<(&[u8], usize)>,
+    ) {
+        let pack_layered = PackLayered::new(Box::new([&PACK_OVERLAY, &PACK_BASE]));
+
+        assert_eq!(
+            pack_layered
+                .get_file_layer_index_by_path(path)
+                .map(|(file, layer_index)| (file.content(), layer_index)),
+            expected
+        );
+        assert_eq!(
+            pack_layered.get_layer_index_by_path(path),
+            expected.map(|(_content, layer_index)| layer_index)
+        );
+        assert_eq!(
+            pack_layered.get_file_by_path(path).map(File::content),
+            expected.map(|(content, _layer_index)| content)
+        );
+    }
+
+    #[test]
+    fn empty_resolves_nothing() {
+        let pack_layered = PackLayered::<PackMock>::new(Box::new([]));
+
+        assert!(pack_layered.get_file_by_path("/index.html").is_none());
+    }
+
+    #[test]
+    fn files_skips_shadowed() {
+        let pack_layered = PackLayered::new(Box::new([&PACK_OVERLAY, &PACK_BASE]));
+
+        let mut files = pack_layered
+            .files()
+            .map(|(path, file)| (path, file.content()))
+            .collect::<Vec<_>>();
+        files.sort_unstable();
+        assert_eq!(
+            files,
+            [
+                ("/index.html", b"base-index".as_slice()),
+                ("/logo.svg", b"overlay-logo".as_slice())
+            ]
+        );
+        assert_eq!(pack_layered.len(), 2);
+
+        assert_eq!(
+            pack_layered
+                .files_by_prefix("/logo")
+                .map(|(path, file)| (path, file.content()))
+                .collect::<Vec<_>>(),
+            [("/logo.svg", b"overlay-logo".as_slice())]
+        );
+    }
+}
@@ -0,0 +1,402 @@
+//! Owned packs. Provides [PackOwned], a [Pack] owning its serialized bytes.
+
+use crate::{
+    common::{file::FileArchived, pack::PackArchived},
+    loader::{self, LoadCheckedOptions, LoadError},
+    pack::{Pack, PackEnumerate},
+};
+use anyhow::Error;
+#[cfg(feature = "signature")]
+use ed25519_dalek::VerifyingKey;
+#[cfg(feature = "mmap")]
+pub use memmap2::Mmap;
+use rkyv::util::AlignedVec;
+use std::{fmt, fs, io, ops::Deref, path::Path};
+
+/// Storage for serialized `pack` bytes, owned by [PackOwned].
+///
+/// # Safety
+/// Slice returned by [Self::pack_bytes] must stay valid (and unchanged) until
+/// the object is dropped, even if the object itself is moved. This holds for
+/// heap allocated buffers or memory maps, but not for inline arrays.
+pub unsafe trait PackBytes {
+    /// Returns serialized `pack` bytes.
+    fn pack_bytes(&self) -> &[u8];
+}
+// SAFETY: content is heap allocated, moving [AlignedVec] does not move it
+unsafe impl PackBytes for AlignedVec<{ loader::ALIGN_BYTES }> {
+    fn pack_bytes(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+// SAFETY: static data never moves
+unsafe impl PackBytes for &'static [u8] {
+    fn pack_bytes(&self) -> &[u8] {
+        self
+    }
+}
+// SAFETY: mapping stays in place until unmapped on drop
+#[cfg(feature = "mmap")]
+unsafe impl PackBytes for Mmap {
+    fn pack_bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+/// Error returned when creating [PackOwned] from a file.
+#[derive(Debug)]
+pub enum PackOwnedFileError {
+    /// File could not be opened or read.
+    Io(io::Error),
+    /// File was read, but it does not contain a valid `pack`.
+    Load(LoadError),
+}
+impl fmt::Display for PackOwnedFileError {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            Self::Io(_) => write!(f, "failed to read pack file"),
+            Self::Load(_) => write!(f, "failed to load pack"),
+        }
+    }
+}
+impl std::error::Error for PackOwnedFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Load(error) => Some(error),
+        }
+    }
+}
+
+/// [Pack] owning bytes it was loaded from.
+///
+/// Functions in [crate::loader] return [PackArchived] borrowing serialized
+/// bytes, so the bytes must be kept alive (and usually made `'static`) by the
+/// user. [PackOwned] keeps both together, so it can be freely moved, stored in
+/// [std::sync::Arc] or swapped in runtime (see
+/// [crate::pack_swappable::PackSwappable]).
+///
+/// [PackOwned] dereferences to [PackArchived] and implements [Pack], so it can
+/// be directly used with [crate::responder::Responder].
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut bytes = rkyv::util::AlignedVec::<16>::new();
+/// bytes.extend_from_slice(&std::fs::read(...).unwrap());
+///
+/// let pack = web_static_pack::pack_owned::PackOwned::load_checked(
+///     bytes,
+///     &LoadCheckedOptions::default(),
+/// )
+/// .unwrap();
+///
+/// let responder = web_static_pack::responder::Responder::new(&pack);
+/// ```
+pub struct PackOwned<B = AlignedVec<{ loader::ALIGN_BYTES }>>
+where
+    B: PackBytes,
+{
+    // borrows from `bytes`, lifetime is never exposed as `'static`
+    pack: &'static PackArchived,
+    bytes: B,
+}
+impl<B> PackOwned<B>
+where
+    B: PackBytes,
+{
+    /// Creates new instance from `bytes` and `load` function, mapping them to
+    /// [PackArchived].
+    ///
+    /// This is the most generic constructor, typically one of [Self::load],
+    /// [Self::load_checked] or `Self::load_signed` will be used.
+    pub fn try_new<E>(
+        bytes: B,
+        load: impl for<'s> FnOnce(&'s [u8]) -> Result<&'s PackArchived, E>,
+    ) -> Result<Self, E> {
+        let pack = load(bytes.pack_bytes())?;
+
+        // SAFETY: [PackBytes] guarantees that content stays in place as long as
+        // `bytes` exists. `pack` is stored together with `bytes` and only
+        // accessible through references bound to `self`.
+        let pack = unsafe { &*(pack as *const PackArchived) };
+
+        Ok(Self { pack, bytes })
+    }
+
+    /// Creates new instance with [loader::load].
+    ///
+    /// # Safety
+    /// See [loader::load].
+    pub unsafe fn load(bytes: B) -> Result<Self, Error> {
+        Self::try_new(bytes, |bytes| unsafe { loader::load(bytes) })
+    }
+
+    /// Creates new instance with [loader::load_checked].
+    pub fn load_checked(
+        bytes: B,
+        options: &LoadCheckedOptions,
+    ) -> Result<Self, LoadError> {
+        Self::try_new(bytes, |bytes| loader::load_checked(bytes, options))
+    }
+
+    /// Creates new instance with [loader::load_signed].
+    #[cfg(feature = "signature")]
+    pub fn load_signed(
+        bytes: B,
+        trusted_keys: &[VerifyingKey],
+        options: &LoadCheckedOptions,
+    ) -> Result<Self, LoadError> {
+        Self::try_new(bytes, |bytes| {
+            loader::load_signed(bytes, trusted_keys, options)
+        })
+    }
+
+    /// Returns bytes this `pack` was loaded from.
+    pub fn bytes(&self) -> &B {
+        &self.bytes
+    }
+
+    /// Returns loaded [PackArchived].
+    pub fn pack(&self) -> &PackArchived {
+        self.pack
+    }
+
+    /// Drops loaded [PackArchived], returning bytes it was loaded from.
+    pub fn into_bytes(self) -> B {
+        self.bytes
+    }
+}
+impl PackOwned<AlignedVec<{ loader::ALIGN_BYTES }>> {
+    /// Reads file at `path` into aligned buffer, then loads it with
+    /// [loader::load_checked].
+    pub fn read_file(
+        path: &Path,
+        options: &LoadCheckedOptions,
+    ) -> Result<Self, PackOwnedFileError> {
+        let mut file = fs::File::open(path).map_err(PackOwnedFileError::Io)?;
+        let length = file.metadata().map_err(PackOwnedFileError::Io)?.len();
+
+        let mut bytes = AlignedVec::with_capacity(length as usize);
+        bytes
+            .extend_from_reader(&mut file)
+            .map_err(PackOwnedFileError::Io)?;
+
+        let pack = Self::load_checked(bytes, options).map_err(PackOwnedFileError::Load)?;
+
+        Ok(pack)
+    }
+}
+#[cfg(feature = "mmap")]
+impl PackOwned<Mmap> {
+    /// Maps file at `path` into memory, then loads it with
+    /// [loader::load_checked]. Unlike [PackOwned::read_file], only parts of
+    /// the file actually used are read from disk, but only if
+    /// [LoadCheckedOptions::verify_checksum] (enabled by default) is disabled.
+    ///
+    /// # Safety
+    /// File must not be modified or truncated in place (by this or any other
+    /// process) as long as returned instance exists, as this would change
+    /// already validated content, making it undefined behavior to read.
+    /// Packer never does this, it replaces files atomically (see
+    /// `web_static_pack_packer::pack::store_file`), so existing mappings keep
+    /// pointing to the previous version. If this can not be guaranteed, use
+    /// [PackOwned::read_file].
+    pub unsafe fn map_file(
+        path: &Path,
+        options: &LoadCheckedOptions,
+    ) -> Result<Self, PackOwnedFileError> {
+        let file = fs::File::open(path).map_err(PackOwnedFileError::Io)?;
+
+        // SAFETY: file is not modified while mapped, as required from caller
+        // mmap is page aligned, which satisfies [loader::ALIGN_BYTES]
+        let bytes = unsafe { Mmap::map(&file) }.map_err(PackOwnedFileError::Io)?;
+
+        let pack = Self::load_checked(bytes, options).map_err(PackOwnedFileError::Load)?;
+
+        Ok(pack)
+    }
+}
+impl<B> Deref for PackOwned<B>
+where
+    B: PackBytes,
+{
+    type Target = PackArchived;
+
+    fn deref(&self) -> &Self::Target {
+        self.pack()
+    }
+}
+impl<B> Pack for PackOwned<B>
+where
+    B: PackBytes,
+{
+    type File = FileArchived;
+
+    fn get_file_by_path(
+        &self,
+        path: &str,
+    ) -> Option<&Self::File> {
+        self.pack().get_file_by_path(path)
+    }
+}
+impl<B> PackEnumerate for PackOwned<B>
+where
+    B: PackBytes,
+{
+    fn files(&self) -> impl Iterator<Item = (&str, &Self::File)> {
+        self.pack().files()
+    }
+
+    fn files_by_prefix<'s>(
+        &'s self,
+        prefix: &str,
+    ) -> impl Iterator<Item = (&'s str, &'s Self::File)> {
+        self.pack().files_by_prefix(prefix)
+    }
+
+    fn len(&self) -> usize {
+        self.pack().len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pack().is_empty()
+    }
+}
+impl<B> fmt::Debug for PackOwned<B>
+where
+    B: PackBytes,
+{
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.debug_struct("PackOwned")
+            .field("pack", &self.pack)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test_pack_owned {
+    use super::{PackOwned, PackOwnedFileError};
+    use crate::{
+        common::{
+            PACK_FILE_CHECKSUM_ALGORITHM_NONE, PACK_FILE_MAGIC, PACK_FILE_VERSION,
+            cache_control::CacheControl, file::File, pack::Pack as Pack_, pack_path::PackPath,
+        },
+        loader::{LoadCheckedOptions, LoadError},
+        pack::{Pack, PackEnumerate},
+    };
+    use rkyv::{rancor, util::AlignedVec};
+    use std::{collections::HashMap, env, fs, path::PathBuf, process};
+
+    // builds valid serialized pack, without checksum
+    fn serialized(content: &[u8]) -> AlignedVec {
+        let pack = Pack_ {
+            files_by_path: HashMap::from([(
+                PackPath::from_string("/index.html".to_owned()),
+                File {
+                    content: content.into(),
+                    content_gzip: None,
+                    content_brotli: None,
+                    content_type: "text/html; charset=utf-8".to_owned(),
+                    etag: "\"etag\"".to_owned(),
+                    cache_control: CacheControl::MaxCache,
+                },
+            )]),
+        };
+        let payload = rkyv::to_bytes::<rancor::Error>(&pack).unwrap();
+
+        let mut serialized = AlignedVec::new();
+        serialized.extend_from_slice(&PACK_FILE_MAGIC.to_ne_bytes());
+        serialized.extend_from_slice(&PACK_FILE_VERSION.to_ne_bytes());
+        serialized.extend_from_slice(&PACK_FILE_CHECKSUM_ALGORITHM_NONE.to_ne_bytes());
+        serialized.extend_from_slice(&(payload.len() as u64).to_ne_bytes());
+        serialized.extend_from_slice(&[0; 32]);
+        serialized.extend_from_slice(&payload);
+        serialized
+    }
+
+    #[test]
+    fn load_checked_owns_bytes() {
+        let pack = PackOwned::load_checked(serialized(b"content"), &LoadCheckedOptions::default())
+            .unwrap();
+
+        // moving does not invalidate loaded pack
+        let pack = Box::new(pack);
+
+        assert_eq!(
+            &*pack.get_file_by_path("/index.html").unwrap().content,
+            b"content"
+        );
+        assert_eq!(pack.len(), 1);
+
+        let bytes = pack.into_bytes();
+        assert_eq!(bytes.as_slice(), serialized(b"content").as_slice());
+    }
+
+    #[test]
+    fn load_checked_fails_for_invalid() {
+        let mut serialized = serialized(b"content");
+        serialized[0] ^= 0xff;
+
+        assert!(matches!(
+            PackOwned::load_checked(serialized, &LoadCheckedOptions::default()),
+            Err(LoadError::MagicMismatch)
+        ));
+    }
+
+    fn file_path(name: &str) -> PathBuf {
+        let file_path = env::temp_dir().join(format!(
+            "web-static-pack-pack-owned-{}-{}.pack",
+            name,
+            process::id()
+        ));
+        fs::write(&file_path, serialized(b"content")).unwrap();
+        file_path
+    }
+
+    #[test]
+    fn read_file_loads() {
+        let file_path = file_path("read");
+
+        let pack = PackOwned::read_file(&file_path, &LoadCheckedOptions::default()).unwrap();
+        assert_eq!(
+            &*pack.get_file_by_path("/index.html").unwrap().content,
+            b"content"
+        );
+
+        fs::remove_file(&file_path).unwrap();
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn map_file_loads() {
+        let file_path = file_path("map");
+
+        // SAFETY: file is owned by this test and not modified
+        let pack =
+            unsafe { PackOwned::map_file(&file_path, &LoadCheckedOptions::default()) }.unwrap();
+        assert_eq!(
+            &*pack.get_file_by_path("/index.html").unwrap().content,
+            b"content"
+        );
+
+        fs::remove_file(&file_path).unwrap();
+    }
+
+    #[test]
+    fn read_file_fails_for_missing() {
+        assert!(matches!(
+            PackOwned::read_file(
+                &env::temp_dir().join("web-static-pack-pack-owned-missing.pack"),
+                &LoadCheckedOptions::default()
+            ),
+            Err(PackOwnedFileError::Io(_))
+        ));
+    }
+}
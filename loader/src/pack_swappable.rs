@@ -0,0 +1,119 @@
+//! Swappable packs. Provides [PackSwappable], a holder of current `pack` which
+//! can be replaced in runtime.
+
+use crate::pack::Pack;
+use std::sync::{Arc, RwLock};
+
+/// Holder of current `pack`, which can be atomically replaced in runtime, eg.
+/// when a new UI `pack` is delivered to a long running service.
+///
+/// Users take a snapshot of current `pack` with [Self::current] (cheap, as it
+/// only clones [Arc]) and serve request from it. Replacing `pack` with
+/// [Self::swap] does not affect snapshots already taken, so in-flight
+/// responses keep using the previous `pack`, which is dropped when the last
+/// snapshot is released.
+///
+/// [PackSwappable] itself does not implement [Pack], as references to files
+/// cannot outlive the swap. Typically `P` will be
+/// [crate::pack_owned::PackOwned], so `pack` bytes are released together with
+/// the `pack`.
+///
+/// # Examples
+///
+/// ```ignore
+/// let pack_swappable = PackSwappable::new(PackOwned::load_checked(...).unwrap());
+///
+/// // for each request
+/// let pack = pack_swappable.current();
+/// let responder = Responder::new(&*pack);
+/// let response = responder.respond_flatten(...);
+///
+/// // when new version arrives
+/// pack_swappable.swap(PackOwned::load_checked(...).unwrap());
+/// ```
+#[derive(Debug)]
+pub struct PackSwappable<P>
+where
+    P: Pack,
+{
+    current: RwLock<Arc<P>>,
+}
+impl<P> PackSwappable<P>
+where
+    P: Pack,
+{
+    /// Creates new instance with initial `pack`.
+    pub fn new(pack: P) -> Self {
+        let current = RwLock::new(Arc::new(pack));
+
+        Self { current }
+    }
+
+    /// Returns snapshot of current `pack`.
+    pub fn current(&self) -> Arc<P> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Replaces current `pack` with `pack`, returns previous one. Snapshots
+    /// taken before will still point to the previous `pack`.
+    pub fn swap(
+        &self,
+        pack: P,
+    ) -> Arc<P> {
+        let pack = Arc::new(pack);
+
+        let mut current = self.current.write().unwrap();
+        std::mem::replace(&mut *current, pack)
+    }
+}
+
+#[cfg(test)]
+mod test_pack_swappable {
+    use super::PackSwappable;
+    use crate::{
+        common::{
+            cache_control::CacheControl, file::File, pack::Pack as Pack_, pack_path::PackPath,
+        },
+        pack::Pack,
+    };
+    use std::{collections::HashMap, sync::Arc};
+
+    fn pack(content: &[u8]) -> Pack_ {
+        Pack_ {
+            files_by_path: HashMap::from([(
+                PackPath::from_string("/index.html".to_owned()),
+                File {
+                    content: content.into(),
+                    content_gzip: None,
+                    content_brotli: None,
+                    content_type: "text/html; charset=utf-8".to_owned(),
+                    etag: "\"etag\"".to_owned(),
+                    cache_control: CacheControl::MaxCache,
+                },
+            )]),
+        }
+    }
+
+    #[test]
+    fn swap_keeps_snapshots() {
+        let pack_swappable = PackSwappable::new(pack(b"v1"));
+
+        let snapshot = pack_swappable.current();
+        let file = snapshot.get_file_by_path("/index.html").unwrap();
+
+        let previous = pack_swappable.swap(pack(b"v2"));
+        assert!(Arc::ptr_eq(&previous, &snapshot));
+        drop(previous);
+
+        // old snapshot still points to previous version
+        assert_eq!(&*file.content, b"v1");
+        assert_eq!(
+            &*pack_swappable
+                .current()
+                .get_file_by_path("/index.html")
+                .unwrap()
+                .content,
+            b"v2"
+        );
+    }
+}
@@ -2,19 +2,46 @@
 //! returning http responses.
 
 use crate::{
-    body::Body,
+    body::{Body, BodyOwned},
+    cache_control::CacheControl,
     content_encoding::{ContentContentEncoding, EncodingAccepted},
+    cors::CorsPolicy,
     file::File,
     pack::Pack,
 };
+use bytes::Bytes;
 use http::{
-    HeaderMap, Method, StatusCode, header,
+    HeaderMap, HeaderValue, Method, Request, StatusCode, header,
     response::{Builder as ResponseBuilder, Response as HttpResponse},
 };
+use std::{borrow::Cow, sync::Arc};
 
 /// Http response type specialization.
 pub type Response<'a> = HttpResponse<Body<'a>>;
 
+/// Value of `allow` header, listing http methods supported by [Responder].
+pub const METHODS_ALLOWED: HeaderValue = HeaderValue::from_static("GET, HEAD, OPTIONS");
+
+/// Options for [Responder] and [ResponderOwned].
+///
+/// If not sure what to set here, use [Default].
+#[derive(Default, Debug)]
+pub struct ResponderOptions {
+    /// How to handle query string in [Responder::respond_request].
+    pub query_policy: QueryPolicy,
+
+    /// CORS policy used to add `access-control-*` headers to responses and
+    /// answer preflight requests. [None] disables CORS handling, `OPTIONS`
+    /// requests are answered with `allow` header only.
+    pub cors_policy: Option<CorsPolicy>,
+}
+
+/// [ResponderOptions::default] for [Responder::new].
+static RESPONDER_OPTIONS_DEFAULT: ResponderOptions = ResponderOptions {
+    query_policy: QueryPolicy::Ignore,
+    cors_policy: None,
+};
+
 /// Responder service, providing http response for requests, looking for
 /// [File] in [Pack].
 ///
@@ -24,6 +51,10 @@ pub type Response<'a> = HttpResponse<Body<'a>>;
 /// - [Self::respond_flatten] - like above, but generates default responses also
 ///   for errors.
 ///
+/// Both have [http::Request] based variants, [Self::respond_request] and
+/// [Self::respond_request_flatten], additionally handling uri decoding and
+/// query string.
+///
 /// # Examples
 ///
 /// ```ignore
@@ -67,23 +98,35 @@ where
     P: Pack,
 {
     pack: &'p P,
+    options: &'p ResponderOptions,
 }
 impl<'p, P> Responder<'p, P>
 where
     P: Pack,
 {
-    /// Creates new instance, based on [Pack].
+    /// Creates new instance, based on [Pack], with default options.
     pub const fn new(pack: &'p P) -> Self {
-        Self { pack }
+        Self::new_with_options(pack, &RESPONDER_OPTIONS_DEFAULT)
+    }
+
+    /// Creates new instance, based on [Pack], with `options`.
+    pub const fn new_with_options(
+        pack: &'p P,
+        options: &'p ResponderOptions,
+    ) -> Self {
+        Self { pack, options }
     }
 
     /// Returns http response for given request parts or rust error to be
     /// handled by user.
     ///
     /// Inside this method:
-    /// - Checks http method (accepts GET or HEAD).
+    /// - Checks http method (accepts GET, HEAD or OPTIONS).
     /// - Looks for file inside `pack` passed in constructor.
+    /// - For OPTIONS, returns 204 with `allow` header and, if request is a CORS
+    ///   preflight, headers from [ResponderOptions::cors_policy].
     /// - Checks for `ETag` match (and returns 304).
+    /// - Adds CORS headers from [ResponderOptions::cors_policy].
     /// - Negotiates content encoding.
     /// - Builds final http response containing header and body (if method is
     ///   not HEAD).
@@ -96,10 +139,11 @@ where
         path: &str,
         headers: &HeaderMap,
     ) -> Result<Response<'p>, ResponderRespondError> {
-        // only GET and HEAD are supported
+        // only GET, HEAD and OPTIONS are supported
         let body_in_response = match *method {
             Method::GET => true,
             Method::HEAD => false,
+            Method::OPTIONS => false,
             _ => {
                 return Err(ResponderRespondError::HttpMethodNotSupported);
             }
@@ -113,16 +157,39 @@ where
             }
         };
 
+        // answer OPTIONS only for existing files, so other paths can be
+        // handled by different service
+        if *method == Method::OPTIONS {
+            let mut response = ResponseBuilder::new()
+                .status(StatusCode::NO_CONTENT)
+                .header(header::ALLOW, METHODS_ALLOWED)
+                .body(Body::empty())
+                .unwrap();
+            if let Some(cors_policy) = &self.options.cors_policy {
+                response
+                    .headers_mut()
+                    .extend(cors_policy.preflight_headers(headers));
+            }
+            return Ok(response);
+        }
+
+        // CORS headers are sent with both full and not modified responses
+        let cors_headers = match &self.options.cors_policy {
+            Some(cors_policy) => cors_policy.response_headers(headers),
+            None => HeaderMap::new(),
+        };
+
         // check for possible `ETag`
         // if `ETag` exists and matches current file, return 304
         if let Some(etag_request) = headers.get(header::IF_NONE_MATCH)
             && etag_request.as_bytes() == file.etag().as_bytes()
         {
-            let response = ResponseBuilder::new()
+            let mut response = ResponseBuilder::new()
                 .status(StatusCode::NOT_MODIFIED)
                 .header(header::ETAG, file.etag()) // https://stackoverflow.com/a/4226409/1658328
                 .body(Body::empty())
                 .unwrap();
+            response.headers_mut().extend(cors_headers);
             return Ok(response);
         };
 
@@ -136,7 +203,7 @@ where
         );
 
         // build final response
-        let response = ResponseBuilder::new()
+        let mut response = ResponseBuilder::new()
             .header(header::CONTENT_TYPE, file.content_type())
             .header(header::ETAG, file.etag())
             .header(header::CACHE_CONTROL, file.cache_control().cache_control())
@@ -154,6 +221,7 @@ where
                 Body::empty()
             })
             .unwrap();
+        response.headers_mut().extend(cors_headers);
 
         Ok(response)
     }
@@ -174,12 +242,333 @@ where
             Err(responder_error) => responder_error.into_response(),
         }
     }
+
+    /// Like [Self::respond], but takes method, path and headers from
+    /// `request`.
+    ///
+    /// Request uri path is percent-decoded before looking for file in `pack`
+    /// (eg. `/file%20name.txt` is resolved to `/file name.txt`), paths not
+    /// decoding to valid utf-8 are rejected with
+    /// [ResponderRespondError::UnparsablePath]. Absolute-form uris (eg.
+    /// `http://example.com/index.html`) are supported. Query string is
+    /// handled according to [ResponderOptions::query_policy].
+    ///
+    /// Request body is ignored.
+    pub fn respond_request<B>(
+        &self,
+        request: &Request<B>,
+    ) -> Result<Response<'p>, ResponderRespondError> {
+        respond_request_with(
+            request,
+            &self.options.query_policy,
+            |method, path, headers| self.respond(method, path, headers),
+        )
+    }
+
+    /// Like [Self::respond_request], but generates "default" responses also
+    /// for errors, as [Self::respond_flatten].
+    pub fn respond_request_flatten<B>(
+        &self,
+        request: &Request<B>,
+    ) -> Response<'p> {
+        match self.respond_request(request) {
+            Ok(response) => response,
+            Err(responder_error) => responder_error.into_response(),
+        }
+    }
+}
+
+/// How query string of request uri is handled by
+/// [Responder::respond_request].
+#[derive(Default, Debug)]
+pub enum QueryPolicy {
+    /// Query string is ignored, file is looked up by path only.
+    #[default]
+    Ignore,
+    /// Query string is ignored when looking up file, but if it contains
+    /// non-empty parameter `parameter_name` (eg. `v` for `?v=hash`), uri is
+    /// assumed to be versioned and response `cache-control` is set like for
+    /// [CacheControl::MaxCache], regardless of file setting.
+    ///
+    /// Please note that parameter value is not compared with file content, so
+    /// responses for outdated version values will be cached as well.
+    CacheBuster {
+        /// Name of query parameter carrying version, eg. `v`.
+        parameter_name: String,
+    },
+}
+
+/// Extracts method, path and headers from `request` for `respond` and applies
+/// `query_policy` to the response.
+fn respond_request_with<B, T>(
+    request: &Request<B>,
+    query_policy: &QueryPolicy,
+    respond: impl FnOnce(&Method, &str, &HeaderMap) -> Result<HttpResponse<T>, ResponderRespondError>,
+) -> Result<HttpResponse<T>, ResponderRespondError> {
+    let uri = request.uri();
+
+    respond_parts_with(
+        request.method(),
+        uri.path(),
+        uri.query(),
+        request.headers(),
+        query_policy,
+        respond,
+    )
+}
+
+/// Like [respond_request_with], but taking request parts, with `path` not yet
+/// percent-decoded.
+fn respond_parts_with<T>(
+    method: &Method,
+    path: &str,
+    query: Option<&str>,
+    headers: &HeaderMap,
+    query_policy: &QueryPolicy,
+    respond: impl FnOnce(&Method, &str, &HeaderMap) -> Result<HttpResponse<T>, ResponderRespondError>,
+) -> Result<HttpResponse<T>, ResponderRespondError> {
+    let path = match path_decode(path) {
+        Some(path) => path,
+        None => return Err(ResponderRespondError::UnparsablePath),
+    };
+
+    let mut response = respond(method, &path, headers)?;
+
+    match query_policy {
+        QueryPolicy::Ignore => {}
+        QueryPolicy::CacheBuster { parameter_name } => {
+            // only responses carrying `cache-control` (eg. not 304) are altered
+            if let Some(query) = query
+                && query_parameter_present(query, parameter_name)
+                && let Some(cache_control) = response.headers_mut().get_mut(header::CACHE_CONTROL)
+            {
+                *cache_control = CacheControl::MaxCache.cache_control();
+            }
+        }
+    }
+
+    Ok(response)
+}
+
+/// Percent-decodes uri `path`. Returns [None] if `path` contains invalid
+/// escape sequence or does not decode to utf-8.
+fn path_decode(path: &str) -> Option<Cow<'_, str>> {
+    if !path.contains('%') {
+        return Some(Cow::Borrowed(path));
+    }
+
+    let path = path.as_bytes();
+    let mut decoded = Vec::with_capacity(path.len());
+
+    let mut index = 0;
+    while index < path.len() {
+        if path[index] == b'%' {
+            let high = (*path.get(index + 1)? as char).to_digit(16)?;
+            let low = (*path.get(index + 2)? as char).to_digit(16)?;
+            decoded.push((high * 16 + low) as u8);
+            index += 3;
+        } else {
+            decoded.push(path[index]);
+            index += 1;
+        }
+    }
+
+    let decoded = String::from_utf8(decoded).ok()?;
+    Some(Cow::Owned(decoded))
+}
+
+/// Checks if `query` contains parameter `name` with non-empty value.
+fn query_parameter_present(
+    query: &str,
+    name: &str,
+) -> bool {
+    query
+        .split('&')
+        .any(|parameter| match parameter.split_once('=') {
+            Some((key, value)) => key == name && !value.is_empty(),
+            None => false,
+        })
+}
+
+/// Http response type specialization for [ResponderOwned].
+pub type ResponseOwned = HttpResponse<BodyOwned>;
+
+/// Like [Responder], but holding `pack` by [Arc] and returning `'static`
+/// responses, with [BodyOwned] keeping the `pack` alive.
+///
+/// Responses can be sent to other tasks / threads and outlive the responder,
+/// which is useful with runtime loaded `pack`s (see
+/// [crate::pack_owned::PackOwned]) and servers requiring `'static` bodies.
+/// Body content is not copied, it points directly into `pack` memory.
+///
+/// Creating [ResponderOwned] is cheap, so with
+/// [crate::pack_swappable::PackSwappable] one can be created for each request
+/// from [crate::pack_swappable::PackSwappable::current].
+///
+/// Responses are the same as generated by [Responder], see
+/// [Responder::respond] for details.
+#[derive(Debug)]
+pub struct ResponderOwned<P>
+where
+    P: Pack,
+{
+    pack: Arc<P>,
+    options: Arc<ResponderOptions>,
+}
+impl<P> Clone for ResponderOwned<P>
+where
+    P: Pack,
+{
+    fn clone(&self) -> Self {
+        Self {
+            pack: self.pack.clone(),
+            options: self.options.clone(),
+        }
+    }
+}
+impl<P> ResponderOwned<P>
+where
+    P: Pack + Send + Sync + 'static,
+{
+    /// Creates new instance, based on [Pack], with default options.
+    pub fn new(pack: Arc<P>) -> Self {
+        Self::new_with_options(pack, Arc::new(ResponderOptions::default()))
+    }
+
+    /// Creates new instance, based on [Pack], with `options`. `options` are
+    /// shared, so instances for different `pack`s can be created cheaply.
+    pub fn new_with_options(
+        pack: Arc<P>,
+        options: Arc<ResponderOptions>,
+    ) -> Self {
+        Self { pack, options }
+    }
+
+    /// Returns `pack` this responder serves from.
+    pub fn pack(&self) -> &Arc<P> {
+        &self.pack
+    }
+
+    /// Like [Responder::respond], but returns `'static` response.
+    pub fn respond(
+        &self,
+        method: &Method,
+        path: &str,
+        headers: &HeaderMap,
+    ) -> Result<ResponseOwned, ResponderRespondError> {
+        let response = Responder::new_with_options(&*self.pack, &self.options)
+            .respond(method, path, headers)?;
+
+        let response = response.map(|body| {
+            if body.data().is_empty() {
+                return BodyOwned::empty();
+            }
+            BodyOwned::new(Bytes::from_owner(PackContent::new(
+                self.pack.clone(),
+                body.data(),
+            )))
+        });
+
+        Ok(response)
+    }
+
+    /// Like [Responder::respond_flatten], but returns `'static` response.
+    pub fn respond_flatten(
+        &self,
+        method: &Method,
+        path: &str,
+        headers: &HeaderMap,
+    ) -> ResponseOwned {
+        match self.respond(method, path, headers) {
+            Ok(response) => response,
+            Err(responder_error) => responder_error.into_response_owned(),
+        }
+    }
+
+    /// Like [Responder::respond_request], but returns `'static` response.
+    pub fn respond_request<B>(
+        &self,
+        request: &Request<B>,
+    ) -> Result<ResponseOwned, ResponderRespondError> {
+        respond_request_with(
+            request,
+            &self.options.query_policy,
+            |method, path, headers| self.respond(method, path, headers),
+        )
+    }
+
+    /// Like [Responder::respond_request_flatten], but returns `'static`
+    /// response.
+    pub fn respond_request_flatten<B>(
+        &self,
+        request: &Request<B>,
+    ) -> ResponseOwned {
+        match self.respond_request(request) {
+            Ok(response) => response,
+            Err(responder_error) => responder_error.into_response_owned(),
+        }
+    }
+
+    /// Like [Self::respond_request_flatten], but taking request parts, for
+    /// frameworks using their own request types. `path` is percent-decoded
+    /// and `query` handled as in [Self::respond_request].
+    #[cfg(feature = "actix")]
+    pub(crate) fn respond_parts_flatten(
+        &self,
+        method: &Method,
+        path: &str,
+        query: Option<&str>,
+        headers: &HeaderMap,
+    ) -> ResponseOwned {
+        let response = respond_parts_with(
+            method,
+            path,
+            query,
+            headers,
+            &self.options.query_policy,
+            |method, path, headers| self.respond(method, path, headers),
+        );
+        match response {
+            Ok(response) => response,
+            Err(responder_error) => responder_error.into_response_owned(),
+        }
+    }
+}
+
+/// [Bytes] owner, a slice of `pack` content kept valid by holding the `pack`.
+struct PackContent<P> {
+    _pack: Arc<P>,
+    data: *const u8,
+    length: usize,
+}
+impl<P> PackContent<P> {
+    /// `data` must be borrowed from `pack`.
+    fn new(
+        pack: Arc<P>,
+        data: &[u8],
+    ) -> Self {
+        Self {
+            _pack: pack,
+            data: data.as_ptr(),
+            length: data.len(),
+        }
+    }
+}
+// SAFETY: only shared access to `pack` content is given, as with `&P`
+unsafe impl<P> Send for PackContent<P> where P: Send + Sync {}
+impl<P> AsRef<[u8]> for PackContent<P> {
+    fn as_ref(&self) -> &[u8] {
+        // SAFETY: `data` was borrowed from `pack`, which is kept alive and
+        // never mutably accessed, as it's shared with [Arc]
+        unsafe { std::slice::from_raw_parts(self.data, self.length) }
+    }
 }
 
 /// Possible errors during [Responder::respond] handling.
 #[derive(PartialEq, Eq, Debug)]
 pub enum ResponderRespondError {
-    /// Not supported HTTP Method, this maps to HTTP `METHOD_NOT_ALLOWED`.
+    /// Not supported HTTP Method, this maps to HTTP `METHOD_NOT_ALLOWED`, with
+    /// `allow` header listing supported methods (see [METHODS_ALLOWED]).
     HttpMethodNotSupported,
 
     /// Request URI was not found in [Pack]. This maps to HTTP `NOT_FOUND`.
@@ -188,6 +577,10 @@ pub enum ResponderRespondError {
     /// Error while parsing HTTP `Accept-Encoding`. This maps to HTTP
     /// `BAD_REQUEST`.
     UnparsableAcceptEncoding,
+
+    /// Request uri path is not valid percent-encoded utf-8. This maps to HTTP
+    /// `BAD_REQUEST`.
+    UnparsablePath,
 }
 impl ResponderRespondError {
     /// Converts error into best matching HTTP error code.
@@ -196,25 +589,57 @@ impl ResponderRespondError {
             ResponderRespondError::HttpMethodNotSupported => StatusCode::METHOD_NOT_ALLOWED,
             ResponderRespondError::PackPathNotFound => StatusCode::NOT_FOUND,
             ResponderRespondError::UnparsableAcceptEncoding => StatusCode::BAD_REQUEST,
+            ResponderRespondError::UnparsablePath => StatusCode::BAD_REQUEST,
         }
     }
 
-    /// Creates default response (status code + empty body) for this error.
+    /// Returns headers required in response for this error, eg. `allow` for
+    /// [ResponderRespondError::HttpMethodNotSupported].
+    pub fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if *self == ResponderRespondError::HttpMethodNotSupported {
+            headers.insert(header::ALLOW, METHODS_ALLOWED);
+        }
+        headers
+    }
+
+    /// Creates default response (status code + required headers + empty body)
+    /// for this error.
     pub fn into_response(&self) -> Response<'static> {
-        let response = ResponseBuilder::new()
+        let mut response = ResponseBuilder::new()
             .status(self.status_code())
             .body(Body::empty())
             .unwrap();
+        *response.headers_mut() = self.headers();
         response
     }
+
+    /// Like [Self::into_response], but for [ResponderOwned].
+    pub fn into_response_owned(&self) -> ResponseOwned {
+        self.into_response().map(|_body| BodyOwned::empty())
+    }
 }
 
 #[cfg(test)]
 mod test_responder {
-    use super::{Responder, ResponderRespondError};
-    use crate::{cache_control::CacheControl, file::File, pack::Pack};
+    use super::{QueryPolicy, Responder, ResponderOptions, ResponderOwned, ResponderRespondError};
+    use crate::{
+        cache_control::CacheControl,
+        common::{
+            cache_control::CacheControl as CacheControl_, file::File as File_, pack::Pack as Pack_,
+            pack_path::PackPath,
+        },
+        cors::{CorsOrigins, CorsPolicy},
+        file::File,
+        pack::Pack,
+        test_fixture::pack_present,
+    };
     use anyhow::anyhow;
-    use http::{HeaderMap, HeaderName, HeaderValue, header, method::Method, status::StatusCode};
+    use http::{
+        HeaderMap, HeaderName, HeaderValue, Request, header, method::Method, status::StatusCode,
+    };
+    use std::sync::Arc;
+    use test_case::test_case;
 
     struct FileMock;
     impl File for FileMock {
@@ -387,6 +812,129 @@ mod test_responder {
 
         let response_flatten = response_error.into_response();
         assert_eq!(response_flatten.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            header_as_string(response_flatten.headers(), header::ALLOW),
+            "GET, HEAD, OPTIONS"
+        );
+    }
+
+    #[test]
+    fn resolves_allow_for_options_request() {
+        let response = RESPONDER
+            .respond(&Method::OPTIONS, "/present", &HeaderMap::default())
+            .unwrap();
+        let headers = response.headers();
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(
+            header_as_string(headers, header::ALLOW), // line break
+            "GET, HEAD, OPTIONS"
+        );
+        assert!(headers.get(header::CONTENT_TYPE).is_none());
+        assert_eq!(response.body().data(), b"");
+
+        let response_error = RESPONDER
+            .respond(&Method::OPTIONS, "/missing", &HeaderMap::default())
+            .unwrap_err();
+        assert_eq!(response_error, ResponderRespondError::PackPathNotFound);
+    }
+
+    #[test_case(None, false; "cors disabled")]
+    #[test_case(Some(CorsOrigins::Any), true; "cors enabled")]
+    fn resolves_preflight_for_options_request(
+        allowed_origins: Option<CorsOrigins>,
+        expected_cors: bool,
+    ) {
+        let options = ResponderOptions {
+            cors_policy: allowed_origins.map(|allowed_origins| CorsPolicy {
+                allowed_origins,
+                allow_credentials: false,
+                expose_headers: Vec::new(),
+                max_age: None,
+            }),
+            ..ResponderOptions::default()
+        };
+        let responder = Responder::new_with_options(&PackMock, &options);
+
+        let response = responder
+            .respond(
+                &Method::OPTIONS,
+                "/present",
+                &[
+                    (
+                        header::ORIGIN,
+                        HeaderValue::from_static("https://app.example.com"),
+                    ),
+                    (
+                        header::ACCESS_CONTROL_REQUEST_METHOD,
+                        HeaderValue::from_static("GET"),
+                    ),
+                ]
+                .into_iter()
+                .collect::<HeaderMap>(),
+            )
+            .unwrap();
+        let headers = response.headers();
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(
+            header_as_string(headers, header::ALLOW), // line break
+            "GET, HEAD, OPTIONS"
+        );
+        assert_eq!(
+            headers.contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN),
+            expected_cors
+        );
+    }
+
+    #[test_case(None; "full")]
+    #[test_case(Some("\"etagvalue\""); "not modified")]
+    fn resolves_cors_headers_for_get_request(if_none_match: Option<&'static str>) {
+        let options = ResponderOptions {
+            cors_policy: Some(CorsPolicy {
+                allowed_origins: CorsOrigins::List(vec!["https://app.example.com".to_owned()]),
+                allow_credentials: true,
+                expose_headers: vec![header::ETAG],
+                max_age: None,
+            }),
+            ..ResponderOptions::default()
+        };
+        let responder = Responder::new_with_options(&PackMock, &options);
+
+        let mut headers = [(
+            header::ORIGIN,
+            HeaderValue::from_static("https://app.example.com"),
+        )]
+        .into_iter()
+        .collect::<HeaderMap>();
+        if let Some(if_none_match) = if_none_match {
+            headers.insert(
+                header::IF_NONE_MATCH,
+                HeaderValue::from_static(if_none_match),
+            );
+        }
+
+        let response = responder
+            .respond(&Method::GET, "/present", &headers)
+            .unwrap();
+        let headers = response.headers();
+
+        assert_eq!(
+            header_as_string(headers, header::ACCESS_CONTROL_ALLOW_ORIGIN),
+            "https://app.example.com"
+        );
+        assert_eq!(
+            header_as_string(headers, header::ACCESS_CONTROL_ALLOW_CREDENTIALS),
+            "true"
+        );
+        assert_eq!(
+            header_as_string(headers, header::ACCESS_CONTROL_EXPOSE_HEADERS),
+            "etag"
+        );
+        assert_eq!(
+            header_as_string(headers, header::VARY), // line break
+            "origin"
+        );
     }
 
     #[test]
@@ -399,4 +947,118 @@ mod test_responder {
         let response_flatten = response_error.into_response();
         assert_eq!(response_flatten.status(), StatusCode::NOT_FOUND);
     }
+
+    #[test]
+    fn responder_owned_response_outlives_pack() {
+        let pack = pack_present();
+        let responder = ResponderOwned::new(Arc::new(pack));
+
+        let response = responder.respond_flatten(&Method::GET, "/present", &HeaderMap::default());
+        let response_missing =
+            responder.respond_flatten(&Method::GET, "/missing", &HeaderMap::default());
+
+        // last reference to `pack` is now held by response body
+        drop(responder);
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body().data(), b"content-identity");
+
+        assert_eq!(response_missing.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response_missing.body().data(), b"");
+    }
+
+    fn pack_no_cache() -> Pack_ {
+        let files_by_path = ["/present", "/file name.txt", "/zażółć.txt"]
+            .into_iter()
+            .map(|path| {
+                (
+                    PackPath::from_string(path.to_owned()),
+                    File_ {
+                        content: path.as_bytes().into(),
+                        content_gzip: None,
+                        content_brotli: None,
+                        content_type: "text/plain; charset=utf-8".to_owned(),
+                        etag: "\"etagvalue\"".to_owned(),
+                        cache_control: CacheControl_::NoCache,
+                    },
+                )
+            })
+            .collect();
+
+        Pack_ { files_by_path }
+    }
+
+    #[test_case("/present", Ok("/present"); "plain path")]
+    #[test_case("http://example.com/present?v=1", Ok("/present"); "absolute form with query")]
+    #[test_case("/file%20name.txt", Ok("/file name.txt"); "percent encoded space")]
+    #[test_case("/za%C5%BC%C3%B3%C5%82%C4%87.txt", Ok("/zażółć.txt"); "percent encoded utf8")]
+    #[test_case("/file%2", Err(ResponderRespondError::UnparsablePath); "truncated escape")]
+    #[test_case("/file%zz", Err(ResponderRespondError::UnparsablePath); "invalid escape")]
+    #[test_case("/file%FF", Err(ResponderRespondError::UnparsablePath); "invalid utf8")]
+    #[test_case("/missing?v=1", Err(ResponderRespondError::PackPathNotFound); "missing")]
+    fn respond_request_resolves_path(
+        uri: &str,
+        expected: Result<&str, ResponderRespondError>,
+    ) {
+        let pack = pack_no_cache();
+        let responder = Responder::new(&pack);
+
+        let request = Request::get(uri).body(()).unwrap();
+        let content = responder
+            .respond_request(&request)
+            .map(|response| str::from_utf8(response.body().data()).unwrap());
+
+        assert_eq!(content, expected);
+    }
+
+    #[test_case(QueryPolicy::Ignore, "/present?v=abc", "no-cache"; "ignore")]
+    #[test_case(query_policy_cache_buster(), "/present", "no-cache"; "cache buster without query")]
+    #[test_case(query_policy_cache_buster(), "/present?v=", "no-cache"; "cache buster empty value")]
+    #[test_case(query_policy_cache_buster(), "/present?version=abc", "no-cache"; "cache buster other parameter")]
+    #[test_case(query_policy_cache_buster(), "/present?x=1&v=abc", "max-age=31536000, immutable"; "cache buster")]
+    fn respond_request_applies_query_policy(
+        query_policy: QueryPolicy,
+        uri: &str,
+        expected: &str,
+    ) {
+        let pack = pack_no_cache();
+        let options = ResponderOptions {
+            query_policy,
+            ..ResponderOptions::default()
+        };
+        let responder = Responder::new_with_options(&pack, &options);
+
+        let request = Request::get(uri).body(()).unwrap();
+        let response = responder.respond_request(&request).unwrap();
+
+        assert_eq!(
+            header_as_string(response.headers(), header::CACHE_CONTROL),
+            expected
+        );
+    }
+
+    #[test]
+    fn responder_owned_respond_request_applies_query_policy() {
+        let responder = ResponderOwned::new_with_options(
+            Arc::new(pack_no_cache()),
+            Arc::new(ResponderOptions {
+                query_policy: query_policy_cache_buster(),
+                ..ResponderOptions::default()
+            }),
+        );
+
+        let request = Request::get("/present?v=abc").body(()).unwrap();
+        let response = responder.respond_request_flatten(&request);
+
+        assert_eq!(
+            header_as_string(response.headers(), header::CACHE_CONTROL),
+            "max-age=31536000, immutable"
+        );
+    }
+
+    fn query_policy_cache_buster() -> QueryPolicy {
+        QueryPolicy::CacheBuster {
+            parameter_name: "v".to_owned(),
+        }
+    }
 }
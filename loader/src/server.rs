@@ -0,0 +1,417 @@
+//! Ready-made hyper server, enabled with `hyper-server` feature. Provides
+//! [serve], [serve_listener] and [serve_listener_with], serving `pack` over
+//! HTTP/1 and HTTP/2 until shutdown is requested.
+
+use crate::{
+    pack::Pack,
+    responder::{ResponderOwned, ResponseOwned},
+};
+use hyper::{Request, body::Incoming, service::service_fn};
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo, TokioTimer},
+    server::{conn::auto::Builder, graceful::GracefulShutdown},
+};
+use std::{
+    convert::Infallible,
+    future::{Future, ready},
+    io,
+    net::SocketAddr,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    net::TcpListener,
+    pin, select,
+    sync::Semaphore,
+    task::JoinSet,
+    time::{sleep, timeout},
+};
+
+/// Options for [serve], [serve_listener] and [serve_listener_with].
+///
+/// If not sure what to set here, use [Default].
+#[derive(Debug)]
+pub struct ServeOptions {
+    /// Maximum number of connections served at the same time. When reached,
+    /// new connections are not accepted until one of existing is closed.
+    /// [None] means no limit.
+    pub connections_max: Option<usize>,
+
+    /// How long to wait for active connections to complete after shutdown was
+    /// requested. Connections still active after this time are aborted. [None]
+    /// means waiting until all connections are closed.
+    pub shutdown_timeout: Option<Duration>,
+
+    /// How long to wait for client to send request headers, counted from
+    /// connection start or, for kept alive HTTP/1 connections, from end of
+    /// previous response. Connections not sending headers in time are closed,
+    /// so idle or slow clients can not occupy all
+    /// [ServeOptions::connections_max] slots. [None] disables the timeout.
+    pub header_read_timeout: Option<Duration>,
+
+    /// Interval of HTTP/2 keep-alive pings. Connections not responding to ping
+    /// within [ServeOptions::http2_keep_alive_timeout] are closed. [None]
+    /// disables pings.
+    pub http2_keep_alive_interval: Option<Duration>,
+    /// How long to wait for HTTP/2 keep-alive ping response, see
+    /// [ServeOptions::http2_keep_alive_interval].
+    pub http2_keep_alive_timeout: Duration,
+}
+impl Default for ServeOptions {
+    fn default() -> Self {
+        Self {
+            connections_max: Some(1024),
+            shutdown_timeout: Some(Duration::from_secs(10)),
+            header_read_timeout: Some(Duration::from_secs(30)),
+            http2_keep_alive_interval: Some(Duration::from_secs(20)),
+            http2_keep_alive_timeout: Duration::from_secs(20),
+        }
+    }
+}
+
+/// Binds to `bind` address and serves `pack` with [serve_listener]. Returns
+/// error only if binding fails.
+///
+/// # Examples
+///
+/// ```ignore
+/// let pack = PackOwned::read_file(...).unwrap();
+///
+/// serve(
+///     SocketAddr::from((Ipv4Addr::LOCALHOST, 8080)),
+///     ResponderOwned::new(Arc::new(pack)),
+///     async { tokio::signal::ctrl_c().await.unwrap() },
+///     &ServeOptions::default(),
+/// )
+/// .await
+/// .unwrap();
+/// ```
+pub async fn serve<P, S>(
+    bind: SocketAddr,
+    responder: ResponderOwned<P>,
+    shutdown: S,
+    options: &ServeOptions,
+) -> Result<(), io::Error>
+where
+    P: Pack + Send + Sync + 'static,
+    S: Future<Output = ()>,
+{
+    let listener = TcpListener::bind(bind).await?;
+
+    serve_listener(listener, responder, shutdown, options).await;
+
+    Ok(())
+}
+
+/// Serves `pack` with [ResponderOwned::respond_request_flatten] on connections
+/// accepted from `listener`, until `shutdown` completes. Useful when
+/// `listener` needs to be created upfront, eg. to bind to ephemeral port and
+/// read it with [TcpListener::local_addr].
+///
+/// See [serve_listener_with] for details.
+pub async fn serve_listener<P, S>(
+    listener: TcpListener,
+    responder: ResponderOwned<P>,
+    shutdown: S,
+    options: &ServeOptions,
+) where
+    P: Pack + Send + Sync + 'static,
+    S: Future<Output = ()>,
+{
+    serve_listener_with(
+        listener,
+        move |request, _remote_address| responder.respond_request_flatten(&request),
+        shutdown,
+        options,
+    )
+    .await
+}
+
+/// Like [serve_listener], but responds with `respond` function, called with
+/// request and address of the client. Allows customizing responses (eg.
+/// rewriting paths, access logging) while keeping server handling.
+///
+/// Each connection is served with HTTP/1 or HTTP/2, depending on what client
+/// uses.
+///
+/// When `shutdown` completes, no more connections are accepted, active ones
+/// are gracefully closed (see [ServeOptions::shutdown_timeout]) and the
+/// function returns. No connection tasks are left running after return.
+///
+/// Connections of clients not sending requests are closed after
+/// [ServeOptions::header_read_timeout], HTTP/2 connections are additionally
+/// checked with keep-alive pings.
+///
+/// Errors of single connections (eg. client disconnecting) are ignored. So are
+/// errors while accepting connections (eg. connection reset before accepted),
+/// server waits a moment before accepting again if they are caused by
+/// resources exhaustion (eg. open files limit reached).
+pub async fn serve_listener_with<R, S>(
+    listener: TcpListener,
+    respond: R,
+    shutdown: S,
+    options: &ServeOptions,
+) where
+    R: Fn(Request<Incoming>, SocketAddr) -> ResponseOwned + Clone + Send + Sync + 'static,
+    S: Future<Output = ()>,
+{
+    pin!(shutdown);
+
+    let connections_semaphore = options
+        .connections_max
+        .map(|connections_max| Arc::new(Semaphore::new(connections_max)));
+
+    let mut builder = Builder::new(TokioExecutor::new());
+    builder
+        .http1()
+        .timer(TokioTimer::new())
+        .header_read_timeout(options.header_read_timeout);
+    builder
+        .http2()
+        .timer(TokioTimer::new())
+        .keep_alive_interval(options.http2_keep_alive_interval)
+        .keep_alive_timeout(options.http2_keep_alive_timeout);
+    let builder = Arc::new(builder);
+
+    let graceful = GracefulShutdown::new();
+    let mut connections = JoinSet::new();
+
+    loop {
+        // collect finished connection tasks
+        while connections.try_join_next().is_some() {}
+
+        // wait for free connection slot, if limited
+        let connection_permit = match &connections_semaphore {
+            Some(connections_semaphore) => select! {
+                connection_permit = connections_semaphore.clone().acquire_owned() => {
+                    Some(connection_permit.unwrap()) // semaphore is never closed
+                }
+                () = &mut shutdown => break,
+            },
+            None => None,
+        };
+
+        let (stream, remote_address) = select! {
+            result = listener.accept() => match result {
+                Ok(stream_remote_address) => stream_remote_address,
+                Err(error) if accept_error_is_connection(&error) => continue,
+                Err(_) => {
+                    // probably resources exhaustion, give them a chance to be freed
+                    select! {
+                        () = sleep(ACCEPT_ERROR_DELAY) => continue,
+                        () = &mut shutdown => break,
+                    }
+                }
+            },
+            () = &mut shutdown => break,
+        };
+
+        let respond = respond.clone();
+        let service = service_fn(move |request: Request<Incoming>| {
+            let response = respond(request, remote_address);
+            ready(Ok::<_, Infallible>(response))
+        });
+
+        let builder = builder.clone();
+        let header_read_timeout = options.header_read_timeout;
+        let watcher = graceful.watcher();
+
+        connections.spawn(async move {
+            // detecting protocol version does not time out, so wait for
+            // anything to be sent first
+            if let Some(header_read_timeout) = header_read_timeout
+                && timeout(header_read_timeout, stream.readable())
+                    .await
+                    .is_err()
+            {
+                return;
+            }
+
+            let connection = builder.serve_connection(TokioIo::new(stream), service);
+
+            // connection errors are client side problems
+            let _ = watcher.watch(connection).await;
+            drop(connection_permit);
+        });
+    }
+
+    match options.shutdown_timeout {
+        Some(shutdown_timeout) => {
+            let _ = timeout(shutdown_timeout, graceful.shutdown()).await;
+        }
+        None => {
+            graceful.shutdown().await;
+        }
+    }
+
+    // abort connections still active after timeout
+    connections.shutdown().await;
+}
+
+/// Delay before accepting connections again after non-connection accept error.
+const ACCEPT_ERROR_DELAY: Duration = Duration::from_secs(1);
+
+/// Checks if accept `error` concerns only the connection being accepted, so
+/// next one can be accepted immediately.
+fn accept_error_is_connection(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::Interrupted
+    )
+}
+
+#[cfg(test)]
+mod test_server {
+    use super::{ServeOptions, serve_listener};
+    use crate::{responder::ResponderOwned, test_fixture::pack_present};
+    use http_body_util::{BodyExt, Empty};
+    use hyper::{Request, StatusCode, body::Bytes, client::conn::http1};
+    use hyper_util::rt::TokioIo;
+    use std::{net::Ipv4Addr, sync::Arc, time::Duration};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+        sync::oneshot,
+        time::{sleep, timeout},
+    };
+
+    #[tokio::test]
+    async fn serves_until_shutdown() {
+        let pack = pack_present();
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let bind = listener.local_addr().unwrap();
+
+        let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
+        let server = tokio::spawn(async move {
+            serve_listener(
+                listener,
+                ResponderOwned::new(Arc::new(pack)),
+                async {
+                    shutdown_receiver.await.unwrap();
+                },
+                &ServeOptions::default(),
+            )
+            .await;
+        });
+
+        let stream = TcpStream::connect(bind).await.unwrap();
+        let (mut sender, connection) = http1::handshake(TokioIo::new(stream)).await.unwrap();
+        tokio::spawn(connection);
+
+        let response = sender
+            .send_request(
+                Request::get("/present")
+                    .body(Empty::<Bytes>::new())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&*body, b"content-identity");
+        drop(sender);
+
+        shutdown_sender.send(()).unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn aborts_connections_after_shutdown_timeout() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let bind = listener.local_addr().unwrap();
+
+        let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
+        let server = tokio::spawn(async move {
+            serve_listener(
+                listener,
+                ResponderOwned::new(Arc::new(pack_present())),
+                async {
+                    shutdown_receiver.await.unwrap();
+                },
+                &ServeOptions {
+                    shutdown_timeout: Some(Duration::from_millis(100)),
+                    ..ServeOptions::default()
+                },
+            )
+            .await;
+        });
+
+        // request is never completed, so connection can not be closed gracefully
+        let mut stream = TcpStream::connect(bind).await.unwrap();
+        stream
+            .write_all(b"GET /present HTTP/1.1\r\n")
+            .await
+            .unwrap();
+        sleep(Duration::from_millis(100)).await;
+
+        shutdown_sender.send(()).unwrap();
+        timeout(Duration::from_secs(5), server)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // connection task was aborted, closing the connection
+        let mut buffer = Vec::new();
+        let read = timeout(Duration::from_secs(5), stream.read_to_end(&mut buffer))
+            .await
+            .unwrap();
+        assert!(read.is_err() || buffer.is_empty());
+    }
+
+    #[tokio::test]
+    async fn closes_stalled_connections_after_header_read_timeout() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let bind = listener.local_addr().unwrap();
+
+        let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
+        let server = tokio::spawn(async move {
+            serve_listener(
+                listener,
+                ResponderOwned::new(Arc::new(pack_present())),
+                async {
+                    shutdown_receiver.await.unwrap();
+                },
+                &ServeOptions {
+                    connections_max: Some(2),
+                    header_read_timeout: Some(Duration::from_millis(100)),
+                    ..ServeOptions::default()
+                },
+            )
+            .await;
+        });
+
+        // client sending nothing and client never completing request headers
+        for request_part in [&b""[..], b"GET /present HTTP/1.1\r\n"] {
+            let mut stream = TcpStream::connect(bind).await.unwrap();
+            stream.write_all(request_part).await.unwrap();
+
+            let mut buffer = Vec::new();
+            let read = timeout(Duration::from_secs(5), stream.read_to_end(&mut buffer))
+                .await
+                .unwrap();
+            assert!(read.is_err() || buffer.is_empty() || buffer.starts_with(b"HTTP/1.1 408"));
+        }
+
+        // connection slots were freed, server is still serving
+        let stream = TcpStream::connect(bind).await.unwrap();
+        let (mut sender, connection) = http1::handshake(TokioIo::new(stream)).await.unwrap();
+        tokio::spawn(connection);
+        let response = sender
+            .send_request(
+                Request::get("/present")
+                    .body(Empty::<Bytes>::new())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        drop(sender);
+
+        shutdown_sender.send(()).unwrap();
+        server.await.unwrap();
+    }
+}
@@ -0,0 +1,21 @@
+//! Fixtures shared by tests of multiple modules.
+
+use crate::common::{cache_control::CacheControl, file::File, pack::Pack, pack_path::PackPath};
+use std::collections::HashMap;
+
+/// Creates [Pack] with single file `/present`, containing `content-identity`.
+pub fn pack_present() -> Pack {
+    Pack {
+        files_by_path: HashMap::from([(
+            PackPath::from_string("/present".to_owned()),
+            File {
+                content: Box::new(*b"content-identity"),
+                content_gzip: None,
+                content_brotli: None,
+                content_type: "text/plain; charset=utf-8".to_owned(),
+                etag: "\"etagvalue\"".to_owned(),
+                cache_control: CacheControl::MaxCache,
+            },
+        )]),
+    }
+}
@@ -0,0 +1,386 @@
+//! [tower](https://docs.rs/tower) integration, enabled with `tower` feature.
+//! Provides [ResponderService], a [Service] serving `pack`, and
+//! [ResponderLayer], a [Layer] serving `pack` in front of an inner service.
+
+use crate::{
+    body::BodyOwned,
+    pack::Pack,
+    responder::{ResponderOwned, ResponderRespondError, ResponseOwned},
+};
+use bytes::Bytes;
+use http::{Request, Response};
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use pin_project_lite::pin_project;
+use std::{
+    convert::Infallible,
+    future::{Future, Ready, ready},
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// [Service] wrapping [ResponderOwned], responding to all requests with
+/// [ResponderOwned::respond_request_flatten].
+///
+/// Request body is ignored. The service is always ready and never fails.
+///
+/// # Examples
+///
+/// ```ignore
+/// let pack = PackOwned::read_file(...).unwrap();
+/// let service = ResponderService::new(ResponderOwned::new(Arc::new(pack)));
+///
+/// // eg. with axum
+/// let router = Router::new().fallback_service(service);
+/// ```
+#[derive(Debug)]
+pub struct ResponderService<P>
+where
+    P: Pack,
+{
+    responder: ResponderOwned<P>,
+}
+impl<P> Clone for ResponderService<P>
+where
+    P: Pack,
+{
+    fn clone(&self) -> Self {
+        Self {
+            responder: self.responder.clone(),
+        }
+    }
+}
+impl<P> ResponderService<P>
+where
+    P: Pack,
+{
+    /// Creates new instance, based on [ResponderOwned].
+    pub fn new(responder: ResponderOwned<P>) -> Self {
+        Self { responder }
+    }
+}
+impl<P, B> Service<Request<B>> for ResponderService<P>
+where
+    P: Pack + Send + Sync + 'static,
+{
+    type Response = ResponseOwned;
+    type Error = Infallible;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &mut self,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(
+        &mut self,
+        request: Request<B>,
+    ) -> Self::Future {
+        let response = self.responder.respond_request_flatten(&request);
+
+        ready(Ok(response))
+    }
+}
+
+/// [Layer] putting [ResponderOwned] in front of inner service, see
+/// [ResponderFallback].
+///
+/// # Examples
+///
+/// ```ignore
+/// let pack = PackOwned::read_file(...).unwrap();
+/// let layer = ResponderLayer::new(ResponderOwned::new(Arc::new(pack)));
+///
+/// // eg. with axum, static files are served first, then api routes
+/// let router = Router::new()
+///     .route("/api/status", get(status))
+///     .layer(layer);
+/// ```
+#[derive(Debug)]
+pub struct ResponderLayer<P>
+where
+    P: Pack,
+{
+    responder: ResponderOwned<P>,
+}
+impl<P> Clone for ResponderLayer<P>
+where
+    P: Pack,
+{
+    fn clone(&self) -> Self {
+        Self {
+            responder: self.responder.clone(),
+        }
+    }
+}
+impl<P> ResponderLayer<P>
+where
+    P: Pack,
+{
+    /// Creates new instance, based on [ResponderOwned].
+    pub fn new(responder: ResponderOwned<P>) -> Self {
+        Self { responder }
+    }
+}
+impl<P, S> Layer<S> for ResponderLayer<P>
+where
+    P: Pack,
+{
+    type Service = ResponderFallback<P, S>;
+
+    fn layer(
+        &self,
+        inner: S,
+    ) -> Self::Service {
+        ResponderFallback {
+            responder: self.responder.clone(),
+            inner,
+        }
+    }
+}
+
+/// [Service] responding from `pack` if possible, falling through to inner
+/// service otherwise. Created by [ResponderLayer].
+///
+/// Request is passed to inner service if [ResponderOwned::respond_request]
+/// fails with [ResponderRespondError::PackPathNotFound] (path not found in
+/// `pack`) or [ResponderRespondError::HttpMethodNotSupported] (eg. `POST` to
+/// api route). Other errors are handled with default responses, like in
+/// [ResponderOwned::respond_request_flatten].
+///
+/// Readiness is taken from inner service.
+#[derive(Debug)]
+pub struct ResponderFallback<P, S>
+where
+    P: Pack,
+{
+    responder: ResponderOwned<P>,
+    inner: S,
+}
+impl<P, S> Clone for ResponderFallback<P, S>
+where
+    P: Pack,
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            responder: self.responder.clone(),
+            inner: self.inner.clone(),
+        }
+    }
+}
+impl<P, S, B, BI> Service<Request<B>> for ResponderFallback<P, S>
+where
+    P: Pack + Send + Sync + 'static,
+    S: Service<Request<B>, Response = Response<BI>>,
+    BI: HttpBody<Data = Bytes>,
+{
+    type Response = Response<ResponderFallbackBody<BI>>;
+    type Error = S::Error;
+    type Future = ResponderFallbackFuture<S::Future>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(
+        &mut self,
+        request: Request<B>,
+    ) -> Self::Future {
+        let response = match self.responder.respond_request(&request) {
+            Ok(response) => response,
+            Err(
+                ResponderRespondError::PackPathNotFound
+                | ResponderRespondError::HttpMethodNotSupported,
+            ) => {
+                return ResponderFallbackFuture::Inner {
+                    future: self.inner.call(request),
+                };
+            }
+            Err(responder_error) => responder_error.into_response_owned(),
+        };
+
+        ResponderFallbackFuture::Responder {
+            response: Some(response),
+        }
+    }
+}
+
+pin_project! {
+    /// [Future] returned by [ResponderFallback].
+    #[project = ResponderFallbackFutureProjection]
+    #[derive(Debug)]
+    #[allow(missing_docs)] // pin_project_lite does not allow documenting fields
+    pub enum ResponderFallbackFuture<F> {
+        /// Response generated from `pack`.
+        Responder {
+            response: Option<ResponseOwned>,
+        },
+        /// Inner service future.
+        Inner {
+            #[pin]
+            future: F,
+        },
+    }
+}
+impl<F, BI, E> Future for ResponderFallbackFuture<F>
+where
+    F: Future<Output = Result<Response<BI>, E>>,
+{
+    type Output = Result<Response<ResponderFallbackBody<BI>>, E>;
+
+    fn poll(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Self::Output> {
+        match self.project() {
+            ResponderFallbackFutureProjection::Responder { response } => {
+                let response = response
+                    .take()
+                    .expect("future polled after completion")
+                    .map(|body| ResponderFallbackBody::Responder { body });
+                Poll::Ready(Ok(response))
+            }
+            ResponderFallbackFutureProjection::Inner { future } => future.poll(cx).map(|result| {
+                result.map(|response| response.map(|body| ResponderFallbackBody::Inner { body }))
+            }),
+        }
+    }
+}
+
+pin_project! {
+    /// Response body of [ResponderFallback], either from `pack` or inner
+    /// service.
+    #[project = ResponderFallbackBodyProjection]
+    #[derive(Debug)]
+    #[allow(missing_docs)] // pin_project_lite does not allow documenting fields
+    pub enum ResponderFallbackBody<BI> {
+        /// Body generated from `pack`.
+        Responder {
+            body: BodyOwned,
+        },
+        /// Inner service body.
+        Inner {
+            #[pin]
+            body: BI,
+        },
+    }
+}
+impl<BI> HttpBody for ResponderFallbackBody<BI>
+where
+    BI: HttpBody<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = BI::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        match self.project() {
+            ResponderFallbackBodyProjection::Responder { body } => Pin::new(body)
+                .poll_frame(cx)
+                .map(|frame| frame.map(|frame| Ok(frame.unwrap_or_else(|e| match e {})))),
+            ResponderFallbackBodyProjection::Inner { body } => body.poll_frame(cx),
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match self {
+            ResponderFallbackBody::Responder { body } => body.is_end_stream(),
+            ResponderFallbackBody::Inner { body } => body.is_end_stream(),
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match self {
+            ResponderFallbackBody::Responder { body } => body.size_hint(),
+            ResponderFallbackBody::Inner { body } => body.size_hint(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_tower {
+    use super::{ResponderLayer, ResponderService};
+    use crate::{common::pack::Pack, responder::ResponderOwned, test_fixture::pack_present};
+    use bytes::Bytes;
+    use http::{Method, Request, Response, StatusCode};
+    use http_body_util::{BodyExt, Full};
+    use std::{convert::Infallible, sync::Arc};
+    use tower::{Layer, ServiceExt, service_fn};
+
+    fn responder() -> ResponderOwned<Pack> {
+        let pack = pack_present();
+        ResponderOwned::new(Arc::new(pack))
+    }
+
+    fn request(
+        method: Method,
+        path: &str,
+    ) -> Request<()> {
+        Request::builder()
+            .method(method)
+            .uri(path)
+            .body(())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn service_responds() {
+        let service = ResponderService::new(responder());
+
+        let response = service
+            .clone()
+            .oneshot(request(Method::GET, "/present"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body().data(), b"content-identity");
+
+        let response = service
+            .oneshot(request(Method::GET, "/missing"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn layer_falls_through_to_inner() {
+        let inner = service_fn(|request: Request<()>| async move {
+            let body = format!("inner {} {}", request.method(), request.uri().path());
+            Ok::<_, Infallible>(Response::new(Full::new(Bytes::from(body))))
+        });
+        let service = ResponderLayer::new(responder()).layer(inner);
+
+        let response = service
+            .clone()
+            .oneshot(request(Method::GET, "/present"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&*body, b"content-identity");
+
+        let response = service
+            .clone()
+            .oneshot(request(Method::GET, "/missing"))
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&*body, b"inner GET /missing");
+
+        let response = service
+            .oneshot(request(Method::POST, "/present"))
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&*body, b"inner POST /present");
+    }
+}
@@ -0,0 +1,338 @@
+//! Diff helpers. Contains [diff] function, comparing two [Pack]s.
+
+use crate::{
+    common::pack::Pack,
+    inspect::{FileSummary, length_display},
+};
+use itertools::Itertools;
+use serde::Serialize;
+use std::fmt;
+
+/// Change of a single value between old and new `pack`.
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct ValueChange<T> {
+    /// Value in old `pack`.
+    pub old: T,
+    /// Value in new `pack`.
+    pub new: T,
+}
+impl<T> ValueChange<T>
+where
+    T: PartialEq,
+{
+    /// Creates [self] if values differ, [None] otherwise.
+    pub fn new(
+        old: T,
+        new: T,
+    ) -> Option<Self> {
+        if old != new {
+            Some(Self { old, new })
+        } else {
+            None
+        }
+    }
+}
+impl<T> fmt::Display for ValueChange<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(f, "{} -> {}", self.old, self.new)
+    }
+}
+
+/// Change of content length of one of encodings. [None] means the encoding is
+/// not present in the `pack`.
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct LengthChange {
+    /// Length in old `pack`.
+    pub old: Option<usize>,
+    /// Length in new `pack`.
+    pub new: Option<usize>,
+    /// Difference between new and old length, missing encoding is counted as
+    /// zero.
+    pub delta: i64,
+}
+impl LengthChange {
+    /// Creates [self] if lengths differ, [None] otherwise.
+    pub fn new(
+        old: Option<usize>,
+        new: Option<usize>,
+    ) -> Option<Self> {
+        if old != new {
+            let delta = new.unwrap_or(0) as i64 - old.unwrap_or(0) as i64;
+            Some(Self { old, new, delta })
+        } else {
+            None
+        }
+    }
+}
+impl fmt::Display for LengthChange {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(
+            f,
+            "{} -> {} ({:+})",
+            length_display(self.old),
+            length_display(self.new),
+            self.delta
+        )
+    }
+}
+
+/// Changes of a single file present in both `pack`s. Fields are [None] if
+/// given property did not change.
+#[derive(Serialize, Debug)]
+pub struct FileChange {
+    /// Path inside the `pack`.
+    pub path: String,
+
+    /// `ETag` change, meaning content change.
+    pub etag: Option<ValueChange<String>>,
+
+    /// `identity` content length change.
+    pub content_length: Option<LengthChange>,
+    /// `gzip` content length change.
+    pub content_gzip_length: Option<LengthChange>,
+    /// `brotli` content length change.
+    pub content_brotli_length: Option<LengthChange>,
+
+    /// `content-type` header change.
+    pub content_type: Option<ValueChange<String>>,
+    /// `cache-control` header change.
+    pub cache_control: Option<ValueChange<String>>,
+}
+impl FileChange {
+    /// Compares two summaries of file under the same path. Returns [None] if
+    /// nothing changed.
+    pub fn new(
+        old: FileSummary,
+        new: FileSummary,
+        etag_old: String,
+        etag_new: String,
+    ) -> Option<Self> {
+        let self_ = Self {
+            path: new.path,
+            etag: ValueChange::new(etag_old, etag_new),
+            content_length: LengthChange::new(Some(old.content_length), Some(new.content_length)),
+            content_gzip_length: LengthChange::new(
+                old.content_gzip_length,
+                new.content_gzip_length,
+            ),
+            content_brotli_length: LengthChange::new(
+                old.content_brotli_length,
+                new.content_brotli_length,
+            ),
+            content_type: ValueChange::new(old.content_type, new.content_type),
+            cache_control: ValueChange::new(old.cache_control, new.cache_control),
+        };
+
+        if self_.etag.is_none()
+            && self_.content_length.is_none()
+            && self_.content_gzip_length.is_none()
+            && self_.content_brotli_length.is_none()
+            && self_.content_type.is_none()
+            && self_.cache_control.is_none()
+        {
+            return None;
+        }
+
+        Some(self_)
+    }
+}
+impl fmt::Display for FileChange {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        writeln!(f, "~ {}", self.path)?;
+        if let Some(etag) = &self.etag {
+            writeln!(f, "    etag: {etag}")?;
+        }
+        if let Some(content_length) = &self.content_length {
+            writeln!(f, "    identity: {content_length}")?;
+        }
+        if let Some(content_gzip_length) = &self.content_gzip_length {
+            writeln!(f, "    gzip: {content_gzip_length}")?;
+        }
+        if let Some(content_brotli_length) = &self.content_brotli_length {
+            writeln!(f, "    br: {content_brotli_length}")?;
+        }
+        if let Some(content_type) = &self.content_type {
+            writeln!(f, "    content-type: {content_type}")?;
+        }
+        if let Some(cache_control) = &self.cache_control {
+            writeln!(f, "    cache-control: {cache_control}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of [diff]. All lists are sorted by path.
+#[derive(Serialize, Debug)]
+pub struct Diff {
+    /// Files present only in new `pack`.
+    pub added: Box<[FileSummary]>,
+    /// Files present only in old `pack`.
+    pub removed: Box<[FileSummary]>,
+    /// Files present in both `pack`s, with any of properties changed.
+    pub changed: Box<[FileChange]>,
+}
+impl Diff {
+    /// Returns `true` if `pack`s contain no differences.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+impl fmt::Display for Diff {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        for file in &self.added {
+            writeln!(f, "+ {file}")?;
+        }
+        for file in &self.removed {
+            writeln!(f, "- {file}")?;
+        }
+        for file_change in &self.changed {
+            write!(f, "{file_change}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares `pack_old` with `pack_new`, returning added, removed and changed
+/// files.
+///
+/// Files are considered changed if their content (`ETag`), length of any of
+/// encodings or headers (`content-type`, `cache-control`) changed.
+pub fn diff(
+    pack_old: &Pack,
+    pack_new: &Pack,
+) -> Diff {
+    let added = pack_new
+        .files_by_path
+        .iter()
+        .filter(|(pack_path, _file)| !pack_old.files_by_path.contains_key(&***pack_path))
+        .map(|(pack_path, file)| FileSummary::new(pack_path, file))
+        .sorted_unstable_by(|a, b| a.path.cmp(&b.path))
+        .collect::<Box<[_]>>();
+
+    let removed = pack_old
+        .files_by_path
+        .iter()
+        .filter(|(pack_path, _file)| !pack_new.files_by_path.contains_key(&***pack_path))
+        .map(|(pack_path, file)| FileSummary::new(pack_path, file))
+        .sorted_unstable_by(|a, b| a.path.cmp(&b.path))
+        .collect::<Box<[_]>>();
+
+    let changed = pack_new
+        .files_by_path
+        .iter()
+        .filter_map(|(pack_path, file_new)| {
+            let file_old = pack_old.files_by_path.get(&**pack_path)?;
+
+            FileChange::new(
+                FileSummary::new(pack_path, file_old),
+                FileSummary::new(pack_path, file_new),
+                file_old.etag.clone(),
+                file_new.etag.clone(),
+            )
+        })
+        .sorted_unstable_by(|a, b| a.path.cmp(&b.path))
+        .collect::<Box<[_]>>();
+
+    Diff {
+        added,
+        removed,
+        changed,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{LengthChange, ValueChange, diff};
+    use crate::{
+        common::{cache_control::CacheControl, file::File, pack::Pack, pack_path::PackPath},
+        file_pack_path::FilePackPath,
+        pack::Builder,
+    };
+
+    fn pack(files: &[(&str, &str, CacheControl)]) -> Pack {
+        let mut builder = Builder::new();
+        for (path, etag, cache_control) in files {
+            builder
+                .file_pack_path_add(FilePackPath {
+                    file: File {
+                        content: Box::from(etag.as_bytes()),
+                        content_gzip: None,
+                        content_brotli: None,
+                        content_type: "text/plain; charset=utf-8".to_owned(),
+                        etag: (*etag).to_owned(),
+                        cache_control: *cache_control,
+                    },
+                    pack_path: PackPath::from_string((*path).to_owned()),
+                })
+                .unwrap();
+        }
+        builder.finalize()
+    }
+
+    #[test]
+    fn diff_returns_expected() {
+        let pack_old = pack(&[
+            ("/unchanged", "a", CacheControl::MaxCache),
+            ("/removed", "a", CacheControl::MaxCache),
+            ("/content", "a", CacheControl::MaxCache),
+            ("/cache", "a", CacheControl::MaxCache),
+        ]);
+        let pack_new = pack(&[
+            ("/unchanged", "a", CacheControl::MaxCache),
+            ("/added", "a", CacheControl::MaxCache),
+            ("/content", "bb", CacheControl::MaxCache),
+            ("/cache", "a", CacheControl::NoCache),
+        ]);
+
+        let diff = diff(&pack_old, &pack_new);
+        assert!(!diff.is_empty());
+
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].path, "/added");
+
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].path, "/removed");
+
+        assert_eq!(diff.changed.len(), 2);
+        assert_eq!(diff.changed[0].path, "/cache");
+        assert!(diff.changed[0].etag.is_none());
+        assert_eq!(
+            diff.changed[0].cache_control,
+            Some(ValueChange {
+                old: "max-age=31536000, immutable".to_owned(),
+                new: "no-cache".to_owned()
+            })
+        );
+        assert_eq!(diff.changed[1].path, "/content");
+        assert_eq!(
+            diff.changed[1].content_length,
+            Some(LengthChange {
+                old: Some(1),
+                new: Some(2),
+                delta: 1
+            })
+        );
+    }
+
+    #[test]
+    fn diff_of_same_is_empty() {
+        let pack = pack(&[("/file", "a", CacheControl::MaxCache)]);
+
+        assert!(diff(&pack, &pack).is_empty());
+    }
+}
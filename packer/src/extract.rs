@@ -0,0 +1,318 @@
+//! Extraction helpers. Contains [extract] function, used to write files from
+//! existing [Pack] back to directory.
+
+use crate::{
+    common::pack::Pack,
+    inspect::{self, Show},
+    pack_path,
+};
+use anyhow::{Context, Error, bail};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    ffi::OsString,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Settings for [extract] function.
+///
+/// If not sure what to set here, use [Default].
+#[derive(Default, Debug)]
+pub struct ExtractOptions {
+    /// Write compressed versions of files alongside the original, with `.gz`
+    /// and `.br` suffix added.
+    pub write_compressed: bool,
+}
+
+/// Manifest describing extracted files, returned by [extract]. Contains all
+/// metadata and headers (as in [inspect::show]) by pack path.
+pub type Manifest = BTreeMap<String, Show>;
+
+/// Writes all files from `pack` into `directory_path`, creating directory tree
+/// mirroring pack paths (eg. `/assets/style.css` is written to
+/// `directory_path/assets/style.css`). Returns [Manifest] describing written
+/// files, which can be stored alongside.
+///
+/// All pack paths are validated before anything is written. Fails for paths
+/// that could point outside of `directory_path` (see
+/// [pack_path::to_file_base_relative_path]), paths of files (including
+/// compressed versions) that already exist, paths conflicting with each other
+/// when compared case insensitively (as on case insensitive filesystems) and
+/// files conflicting with directories needed for other files. Existing files
+/// are never overwritten.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use anyhow::Error;
+/// # use std::path::PathBuf;
+/// # use web_static_pack_packer::{
+/// #     extract::{extract, ExtractOptions},
+/// #     pack::load_file,
+/// # };
+/// #
+/// # fn main() -> Result<(), Error> {
+/// #
+/// let pack = load_file(&PathBuf::from("vcard-personal-portfolio.pack"))?;
+///
+/// let manifest = extract(
+///     &pack,
+///     &PathBuf::from("vcard-personal-portfolio"),
+///     &ExtractOptions::default(),
+/// )?;
+/// assert!(manifest.contains_key("/index.html"));
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn extract(
+    pack: &Pack,
+    directory_path: &Path,
+    options: &ExtractOptions,
+) -> Result<Manifest, Error> {
+    // validate all paths upfront, so we don't end with partially extracted pack
+    let files = pack
+        .files_by_path
+        .iter()
+        .map(|(pack_path, file)| {
+            let file_base_relative_path = pack_path::to_file_base_relative_path(pack_path)
+                .with_context(|| pack_path.to_string())?;
+
+            Ok((
+                pack_path,
+                file,
+                directory_path.join(file_base_relative_path),
+            ))
+        })
+        .collect::<Result<Box<[_]>, Error>>()?;
+
+    // check all written paths upfront, for the same reason
+    let mut file_paths = vec![];
+    for (_, file, file_path) in &files {
+        file_paths.push(file_path.clone());
+        if options.write_compressed {
+            if file.content_gzip.is_some() {
+                file_paths.push(path_suffix_add(file_path, ".gz"));
+            }
+            if file.content_brotli.is_some() {
+                file_paths.push(path_suffix_add(file_path, ".br"));
+            }
+        }
+    }
+    file_paths_check(directory_path, &file_paths)?;
+
+    let mut manifest = Manifest::new();
+    for (pack_path, file, file_path) in files {
+        // TODO: move this into try block with shared context
+        let file_error_context = || file_path.to_string_lossy().into_owned();
+
+        if let Some(file_directory_path) = file_path.parent() {
+            fs::create_dir_all(file_directory_path).with_context(file_error_context)?;
+        }
+
+        content_write(&file_path, &file.content).with_context(file_error_context)?;
+        if options.write_compressed {
+            if let Some(content_gzip) = &file.content_gzip {
+                content_write(&path_suffix_add(&file_path, ".gz"), content_gzip)
+                    .with_context(file_error_context)?;
+            }
+            if let Some(content_brotli) = &file.content_brotli {
+                content_write(&path_suffix_add(&file_path, ".br"), content_brotli)
+                    .with_context(file_error_context)?;
+            }
+        }
+
+        manifest.insert(
+            pack_path.to_string(),
+            inspect::show(pack, pack_path).unwrap(),
+        );
+    }
+
+    Ok(manifest)
+}
+
+/// Checks that `file_paths` (inside `directory_path`) can be all written:
+/// neither of them exists, they do not conflict with each other case
+/// insensitively and no file is placed where other needs a directory.
+fn file_paths_check(
+    directory_path: &Path,
+    file_paths: &[PathBuf],
+) -> Result<(), Error> {
+    // case folded path -> original path
+    let mut file_paths_folded = HashMap::<String, &Path>::new();
+    let mut directory_paths_folded = HashSet::<String>::new();
+    for file_path in file_paths {
+        let file_path_folded = path_fold(file_path);
+        if let Some(file_path_other) = file_paths_folded.insert(file_path_folded, file_path) {
+            bail!("{file_path:?} conflicts with {file_path_other:?}");
+        }
+
+        for directory_path_ in file_path
+            .ancestors()
+            .skip(1)
+            .take_while(|directory_path_| *directory_path_ != directory_path)
+        {
+            directory_paths_folded.insert(path_fold(directory_path_));
+
+            // existing files can not be replaced by directories
+            if let Ok(metadata) = fs::symlink_metadata(directory_path_)
+                && !metadata.is_dir()
+            {
+                bail!("{directory_path_:?} exists and is not a directory");
+            }
+        }
+
+        if fs::symlink_metadata(file_path).is_ok() {
+            bail!("{file_path:?} already exists");
+        }
+    }
+
+    for (file_path_folded, file_path) in &file_paths_folded {
+        if directory_paths_folded.contains(file_path_folded) {
+            bail!("{file_path:?} conflicts with directory needed for other file");
+        }
+    }
+
+    Ok(())
+}
+/// Returns `path` case folded, for comparing on case insensitive filesystems.
+fn path_fold(path: &Path) -> String {
+    path.to_string_lossy().to_lowercase()
+}
+
+/// Writes `content` into new file, fails if file already exists.
+fn content_write(
+    path: &Path,
+    content: &[u8],
+) -> Result<(), Error> {
+    let mut file = fs::File::create_new(path)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    drop(file);
+
+    Ok(())
+}
+/// Adds `suffix` to the file name, eg. `style.css` + `.gz` = `style.css.gz`.
+fn path_suffix_add(
+    path: &Path,
+    suffix: &str,
+) -> PathBuf {
+    let mut path = OsString::from(path);
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ExtractOptions, extract};
+    use crate::{
+        common::{cache_control::CacheControl, file::File, pack::Pack, pack_path::PackPath},
+        file_pack_path::FilePackPath,
+        pack::Builder,
+    };
+    use std::{env, fs, process};
+    use test_case::test_case;
+
+    fn pack(paths: &[&str]) -> Pack {
+        let mut builder = Builder::new();
+        for path in paths {
+            builder
+                .file_pack_path_add(FilePackPath {
+                    file: File {
+                        content: Box::from(path.as_bytes()),
+                        content_gzip: Some(Box::new(*b"gzip")),
+                        content_brotli: None,
+                        content_type: "text/plain; charset=utf-8".to_owned(),
+                        etag: "\"etag\"".to_owned(),
+                        cache_control: CacheControl::NoCache,
+                    },
+                    pack_path: PackPath::from_string((*path).to_owned()),
+                })
+                .unwrap();
+        }
+        builder.finalize()
+    }
+
+    #[test]
+    fn extract_writes_files() {
+        let directory_path = env::temp_dir().join(format!(
+            "web-static-pack-extract-writes-files-{}",
+            process::id()
+        ));
+
+        let manifest = extract(
+            &pack(&["/index.html", "/assets/style.css"]),
+            &directory_path,
+            &ExtractOptions {
+                write_compressed: true,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read(directory_path.join("assets").join("style.css")).unwrap(),
+            b"/assets/style.css"
+        );
+        assert_eq!(
+            fs::read(directory_path.join("index.html.gz")).unwrap(),
+            b"gzip"
+        );
+        assert!(!directory_path.join("index.html.br").exists());
+        assert_eq!(manifest["/index.html"].etag, "\"etag\"");
+
+        fs::remove_dir_all(&directory_path).unwrap();
+    }
+
+    #[test]
+    fn extract_fails_for_unsafe_path() {
+        let directory_path = env::temp_dir().join(format!(
+            "web-static-pack-extract-fails-for-unsafe-path-{}",
+            process::id()
+        ));
+
+        assert!(
+            extract(
+                &pack(&["/index.html", "/../escaped.html"]),
+                &directory_path,
+                &ExtractOptions::default(),
+            )
+            .is_err()
+        );
+
+        // nothing should be written
+        assert!(!directory_path.exists());
+    }
+
+    #[test_case(&["/index.html", "/INDEX.html"], false; "case conflict")]
+    #[test_case(&["/index.html", "/index.html.gz"], true; "compressed conflict")]
+    #[test_case(&["/assets", "/assets/style.css"], false; "file directory conflict")]
+    #[test_case(&["/Assets/style.css", "/assets"], false; "file directory case conflict")]
+    #[test_case(&["/index.html", "/existing.html"], false; "existing file")]
+    fn extract_fails_for_conflicting_paths(
+        paths: &[&str],
+        write_compressed: bool,
+    ) {
+        let directory_path = env::temp_dir().join(format!(
+            "web-static-pack-extract-fails-for-conflicting-paths-{}-{}",
+            process::id(),
+            paths.join("").replace('/', "_"),
+        ));
+        fs::create_dir(&directory_path).unwrap();
+        fs::write(directory_path.join("existing.html"), b"existing").unwrap();
+
+        assert!(
+            extract(
+                &pack(paths),
+                &directory_path,
+                &ExtractOptions { write_compressed },
+            )
+            .is_err()
+        );
+
+        // nothing should be written
+        assert_eq!(fs::read_dir(&directory_path).unwrap().count(), 1);
+
+        fs::remove_dir_all(&directory_path).unwrap();
+    }
+}
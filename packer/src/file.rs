@@ -1,11 +1,13 @@
 //! File helpers. Contains [build_from_path] and [build_from_content] functions
 //! to create a [File] from fs / memory content.
 
-use crate::common::{cache_control::CacheControl, file::File};
+use crate::common::{
+    cache_control::CacheControl,
+    file::{File, etag_from_content},
+};
 use anyhow::Error;
 use brotli::enc::BrotliEncoderParams;
-use flate2::{Compression, write::GzEncoder};
-use sha3::{Digest, Sha3_256};
+use flate2::{Compression, GzBuilder};
 use std::{
     fs,
     io::{Cursor, Write},
@@ -202,7 +204,11 @@ fn content_gzip_from_content(content: &[u8]) -> Option<Box<[u8]>> {
         return None;
     }
 
-    let mut content_gzip = GzEncoder::new(Vec::new(), Compression::best());
+    // header fields are fixed, so output depends only on content
+    let mut content_gzip = GzBuilder::new()
+        .mtime(0)
+        .operating_system(255) // unknown
+        .write(Vec::new(), Compression::best());
     content_gzip.write_all(content).unwrap();
     let content_gzip = content_gzip.finish().unwrap().into_boxed_slice();
 
@@ -258,20 +264,12 @@ fn content_type_from_path(path: &Path) -> String {
     }
     content_type
 }
-/// Calculates `ETag` header from file contents.
-fn etag_from_content(content: &[u8]) -> String {
-    let mut etag = Sha3_256::new();
-    etag.update(content);
-    let etag = etag.finalize();
-    let etag = format!("\"{:x}\"", &etag); // `ETag` as "quoted" hex sha3. Quote is required by standard
-    etag
-}
 
 #[cfg(test)]
 mod test {
     use super::{
         BuildFromContentOptions, build_from_content, content_brotli_from_content,
-        content_gzip_from_content, content_type_from_path, etag_from_content,
+        content_gzip_from_content, content_type_from_path,
     };
     use crate::common::file::File;
     use std::path::{Path, PathBuf};
@@ -330,21 +328,6 @@ mod test {
         );
     }
 
-    #[test]
-    fn etag_from_content_returns_expected() {
-        // two identical payloads should produce identical `ETag`
-        // two different payloads should produce different `ETag`
-
-        assert_eq!(
-            etag_from_content(b"lorem ipsum"),
-            etag_from_content(b"lorem ipsum")
-        );
-        assert_ne!(
-            etag_from_content(b"lorem ipsum"),
-            etag_from_content(b"ipsum lorem")
-        );
-    }
-
     #[test_case(
         &PathBuf::from("a.html"),
         "text/html; charset=utf-8";
@@ -0,0 +1,425 @@
+//! Inspection helpers. Contains [list], [show] and [stats] functions
+//! describing contents of an existing [Pack].
+//!
+//! All returned types implement [Serialize] (for machine readable output) and
+//! [fmt::Display] (for human readable output).
+
+use crate::common::{file::File, pack::Pack, pack_path::PackPath};
+use itertools::Itertools;
+use serde::Serialize;
+use std::{collections::BTreeMap, fmt};
+use web_static_pack::cache_control::CacheControl;
+
+/// Summary of a single file inside a `pack`.
+#[derive(Serialize, Debug)]
+pub struct FileSummary {
+    /// Path inside the `pack`.
+    pub path: String,
+
+    /// Length of `identity` content.
+    pub content_length: usize,
+    /// Length of `gzip` content, if present.
+    pub content_gzip_length: Option<usize>,
+    /// Length of `brotli` content, if present.
+    pub content_brotli_length: Option<usize>,
+
+    /// `content-type` header value.
+    pub content_type: String,
+    /// `cache-control` header value.
+    pub cache_control: String,
+}
+impl FileSummary {
+    /// Creates [self] from file and its path.
+    pub fn new(
+        pack_path: &PackPath,
+        file: &File,
+    ) -> Self {
+        Self {
+            path: pack_path.to_string(),
+            content_length: file.content.len(),
+            content_gzip_length: file.content_gzip.as_ref().map(|content| content.len()),
+            content_brotli_length: file.content_brotli.as_ref().map(|content| content.len()),
+            content_type: file.content_type.clone(),
+            cache_control: cache_control_header(file),
+        }
+    }
+}
+impl fmt::Display for FileSummary {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(
+            f,
+            "{}\tidentity={}\tgzip={}\tbr={}\t{}\t{}",
+            self.path,
+            self.content_length,
+            length_display(self.content_gzip_length),
+            length_display(self.content_brotli_length),
+            self.content_type,
+            self.cache_control,
+        )
+    }
+}
+
+/// Result of [list], summary of all files in the `pack`, sorted by path.
+#[derive(Serialize, Debug)]
+#[serde(transparent)]
+pub struct List {
+    /// Summary of each file.
+    pub files: Box<[FileSummary]>,
+}
+impl fmt::Display for List {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        for file in &self.files {
+            writeln!(f, "{file}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Lists all files in `pack`, sorted by path.
+pub fn list(pack: &Pack) -> List {
+    let files = pack
+        .files_by_path
+        .iter()
+        .sorted_unstable_by(|(pack_path_a, _), (pack_path_b, _)| {
+            (***pack_path_a).cmp(&***pack_path_b)
+        })
+        .map(|(pack_path, file)| FileSummary::new(pack_path, file))
+        .collect::<Box<[_]>>();
+
+    List { files }
+}
+
+/// Result of [show], full description of a single file.
+#[derive(Serialize, Debug)]
+pub struct Show {
+    /// Summary of the file.
+    #[serde(flatten)]
+    pub file: FileSummary,
+
+    /// `ETag` header value.
+    pub etag: String,
+
+    /// Headers sent by loader when serving this file, by `content-encoding`
+    /// (`identity`, `gzip`, `br`).
+    pub headers_by_content_encoding: BTreeMap<String, BTreeMap<String, String>>,
+}
+impl fmt::Display for Show {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        writeln!(f, "path: {}", self.file.path)?;
+        writeln!(f, "content-type: {}", self.file.content_type)?;
+        writeln!(f, "etag: {}", self.etag)?;
+        writeln!(f, "cache-control: {}", self.file.cache_control)?;
+        writeln!(f, "length identity: {}", self.file.content_length)?;
+        writeln!(
+            f,
+            "length gzip: {}",
+            length_display(self.file.content_gzip_length)
+        )?;
+        writeln!(
+            f,
+            "length br: {}",
+            length_display(self.file.content_brotli_length)
+        )?;
+        for (content_encoding, headers) in &self.headers_by_content_encoding {
+            writeln!(f)?;
+            writeln!(f, "headers for {content_encoding}:")?;
+            for (name, value) in headers {
+                writeln!(f, "  {name}: {value}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Describes single file from `pack`, including all metadata and headers that
+/// would be sent by loader. Returns [None] if path does not exist in `pack`.
+pub fn show(
+    pack: &Pack,
+    path: &str,
+) -> Option<Show> {
+    let (pack_path, file) = pack.files_by_path.get_key_value(path)?;
+
+    let file_summary = FileSummary::new(pack_path, file);
+
+    let headers_by_content_encoding = [
+        ("identity", Some(&file.content)),
+        ("gzip", file.content_gzip.as_ref()),
+        ("br", file.content_brotli.as_ref()),
+    ]
+    .into_iter()
+    .filter_map(|(content_encoding, content)| {
+        let content = content?;
+
+        let headers = [
+            ("content-type", file.content_type.clone()),
+            ("etag", file.etag.clone()),
+            ("cache-control", file_summary.cache_control.clone()),
+            ("content-length", content.len().to_string()),
+            ("content-encoding", content_encoding.to_owned()),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_owned(), value))
+        .collect::<BTreeMap<_, _>>();
+
+        Some((content_encoding.to_owned(), headers))
+    })
+    .collect::<BTreeMap<_, _>>();
+
+    Some(Show {
+        file: file_summary,
+        etag: file.etag.clone(),
+        headers_by_content_encoding,
+    })
+}
+
+/// Sizes statistics for group of files.
+#[derive(Serialize, Default, Debug)]
+pub struct SizeStats {
+    /// Number of files.
+    pub files_count: usize,
+
+    /// Total length of `identity` contents.
+    pub content_length: usize,
+    /// Total length of `gzip` contents (only for files having `gzip` version).
+    pub content_gzip_length: usize,
+    /// Total length of `brotli` contents (only for files having `brotli`
+    /// version).
+    pub content_brotli_length: usize,
+    /// Total length of the smallest available content for each file.
+    pub content_best_length: usize,
+
+    /// Ratio of [Self::content_best_length] to [Self::content_length], eg.
+    /// `0.25` if files are compressed to a quarter of original size.
+    pub compression_ratio: f64,
+}
+impl SizeStats {
+    fn add(
+        &mut self,
+        file: &File,
+    ) {
+        let content_gzip_length = file.content_gzip.as_ref().map(|content| content.len());
+        let content_brotli_length = file.content_brotli.as_ref().map(|content| content.len());
+
+        self.files_count += 1;
+        self.content_length += file.content.len();
+        self.content_gzip_length += content_gzip_length.unwrap_or(0);
+        self.content_brotli_length += content_brotli_length.unwrap_or(0);
+        self.content_best_length += [
+            Some(file.content.len()),
+            content_gzip_length,
+            content_brotli_length,
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+        .unwrap();
+
+        self.compression_ratio = if self.content_length > 0 {
+            self.content_best_length as f64 / self.content_length as f64
+        } else {
+            1.0
+        };
+    }
+}
+impl fmt::Display for SizeStats {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(
+            f,
+            "files={}\tidentity={}\tgzip={}\tbr={}\tbest={}\tratio={:.3}",
+            self.files_count,
+            self.content_length,
+            self.content_gzip_length,
+            self.content_brotli_length,
+            self.content_best_length,
+            self.compression_ratio,
+        )
+    }
+}
+
+/// Result of [stats], sizes statistics of the whole `pack`.
+#[derive(Serialize, Debug)]
+pub struct Stats {
+    /// Statistics for all files.
+    pub total: SizeStats,
+    /// Statistics by mime type (`content-type` without parameters, eg.
+    /// `text/html`).
+    pub by_mime_type: BTreeMap<String, SizeStats>,
+    /// The largest (by `identity` content) files, the largest first.
+    pub largest_files: Box<[FileSummary]>,
+}
+impl fmt::Display for Stats {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        writeln!(f, "total:")?;
+        writeln!(f, "  {}", self.total)?;
+        writeln!(f)?;
+        writeln!(f, "by mime type:")?;
+        for (mime_type, size_stats) in &self.by_mime_type {
+            writeln!(f, "  {mime_type}\t{size_stats}")?;
+        }
+        writeln!(f)?;
+        writeln!(f, "largest files:")?;
+        for file in &self.largest_files {
+            writeln!(f, "  {file}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Calculates sizes statistics for `pack`, including up to
+/// `largest_files_count` largest files.
+pub fn stats(
+    pack: &Pack,
+    largest_files_count: usize,
+) -> Stats {
+    let mut total = SizeStats::default();
+    let mut by_mime_type = BTreeMap::<String, SizeStats>::new();
+
+    for file in pack.files_by_path.values() {
+        total.add(file);
+
+        let mime_type = match file.content_type.split_once(';') {
+            Some((mime_type, _parameters)) => mime_type,
+            None => &file.content_type,
+        }
+        .trim()
+        .to_owned();
+        by_mime_type.entry(mime_type).or_default().add(file);
+    }
+
+    let largest_files = pack
+        .files_by_path
+        .iter()
+        .sorted_unstable_by(|(pack_path_a, file_a), (pack_path_b, file_b)| {
+            file_b
+                .content
+                .len()
+                .cmp(&file_a.content.len())
+                .then_with(|| (***pack_path_a).cmp(&***pack_path_b))
+        })
+        .take(largest_files_count)
+        .map(|(pack_path, file)| FileSummary::new(pack_path, file))
+        .collect::<Box<[_]>>();
+
+    Stats {
+        total,
+        by_mime_type,
+        largest_files,
+    }
+}
+
+/// Creates `cache-control` header value for the file, the same as loader
+/// would.
+fn cache_control_header(file: &File) -> String {
+    CacheControl::from(file.cache_control)
+        .cache_control()
+        .to_str()
+        .unwrap()
+        .to_owned()
+}
+/// Formats optional length, using `-` for missing one.
+pub(crate) fn length_display(length: Option<usize>) -> String {
+    match length {
+        Some(length) => length.to_string(),
+        None => "-".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{list, show, stats};
+    use crate::{
+        common::{cache_control::CacheControl, file::File, pack::Pack, pack_path::PackPath},
+        file_pack_path::FilePackPath,
+        pack::Builder,
+    };
+
+    fn pack() -> Pack {
+        let mut builder = Builder::new();
+        for (path, content_type, content_gzip) in [
+            ("/index.html", "text/html; charset=utf-8", Some(2)),
+            ("/style.css", "text/css; charset=utf-8", None),
+            ("/about.html", "text/html; charset=utf-8", Some(4)),
+        ] {
+            builder
+                .file_pack_path_add(FilePackPath {
+                    file: File {
+                        content: Box::new([0; 10]),
+                        content_gzip: content_gzip.map(|length| vec![0; length].into_boxed_slice()),
+                        content_brotli: None,
+                        content_type: content_type.to_owned(),
+                        etag: "\"etag\"".to_owned(),
+                        cache_control: CacheControl::NoCache,
+                    },
+                    pack_path: PackPath::from_string(path.to_owned()),
+                })
+                .unwrap();
+        }
+        builder.finalize()
+    }
+
+    #[test]
+    fn list_returns_sorted() {
+        let list = list(&pack());
+
+        assert_eq!(
+            list.files
+                .iter()
+                .map(|file| file.path.as_str())
+                .collect::<Vec<_>>(),
+            ["/about.html", "/index.html", "/style.css"]
+        );
+        assert_eq!(list.files[0].content_gzip_length, Some(4));
+        assert_eq!(list.files[0].cache_control, "no-cache");
+    }
+
+    #[test]
+    fn show_returns_headers_for_available_encodings() {
+        let pack = pack();
+
+        let show = show(&pack, "/index.html").unwrap();
+        assert_eq!(
+            show.headers_by_content_encoding
+                .keys()
+                .map(String::as_str)
+                .collect::<Vec<_>>(),
+            ["gzip", "identity"]
+        );
+        assert_eq!(
+            show.headers_by_content_encoding["gzip"]["content-length"],
+            "2"
+        );
+
+        assert!(super::show(&pack, "/missing").is_none());
+    }
+
+    #[test]
+    fn stats_returns_expected() {
+        let stats = stats(&pack(), 1);
+
+        assert_eq!(stats.total.files_count, 3);
+        assert_eq!(stats.total.content_length, 30);
+        assert_eq!(stats.total.content_best_length, 2 + 10 + 4);
+
+        let stats_html = &stats.by_mime_type["text/html"];
+        assert_eq!(stats_html.files_count, 2);
+        assert_eq!(stats_html.compression_ratio, 0.3);
+
+        assert_eq!(stats.largest_files.len(), 1);
+        assert_eq!(stats.largest_files[0].path, "/about.html");
+    }
+}
@@ -50,6 +50,68 @@
 //! - `files-stdin [OPTIONS] <INPUT_BASE_DIRECTORY_PATH> <OUTPUT_FILE_PATH>`
 //!   lets you provide list of files from stdin.
 //!
+//! By default BLAKE3 checksum of the `pack` content is stored in its header, so
+//! corruption (eg. partial write) can be detected when loading. It can be
+//! disabled with `--checksum false`.
+//!
+//! Output `pack` is written atomically (to a temporary file, then renamed), so
+//! applications reading (eg. mmap'ing) the previous version never observe a
+//! partially written file.
+//!
+//! There are also subcommands working with already created `pack`s. Files are
+//! copied as they are, without recalculating compressed versions:
+//! - `merge [OPTIONS] <OUTPUT_FILE_PATH> <INPUT_FILE_PATHS>...` combines
+//!   multiple `pack`s into one. `--conflict-policy` (`error`, `first-wins`,
+//!   `last-wins`) decides what happens if the same path exists in multiple
+//!   `pack`s.
+//! - `filter [OPTIONS] <INPUT_FILE_PATH> <OUTPUT_FILE_PATH> <PATTERNS>...`
+//!   creates a `pack` containing only paths matching any of glob patterns, eg.
+//!   `/assets/**`. With `--invert` keeps paths not matching instead.
+//! - `split <INPUT_FILE_PATH> <OUTPUT_MATCHING_FILE_PATH>
+//!   <OUTPUT_REST_FILE_PATH> <PATTERNS>...` splits a `pack` into two, by paths
+//!   matching glob patterns.
+//!
+//! To see what is inside a `pack` (all of them accept `--format json` for
+//! machine readable output):
+//! - `list [OPTIONS] <INPUT_FILE_PATH>` lists all paths with sizes of each
+//!   encoding, `content-type` and `cache-control`.
+//! - `show [OPTIONS] <INPUT_FILE_PATH> <PATH>` shows all metadata and headers
+//!   of a single file.
+//! - `stats [OPTIONS] <INPUT_FILE_PATH>` shows total sizes, compression ratio
+//!   per mime type and the largest files.
+//! - `extract [OPTIONS] <INPUT_FILE_PATH> <OUTPUT_DIRECTORY_PATH>` writes files
+//!   back into directory tree mirroring paths inside `pack`. Optionally writes
+//!   `.gz` / `.br` versions alongside (`--write-compressed`) and a json
+//!   manifest with headers of all files (`--manifest-file-path`).
+//! - `diff [OPTIONS] <OLD_FILE_PATH> <NEW_FILE_PATH>` compares two `pack`s,
+//!   listing added and removed paths, paths with changed content (by `ETag`),
+//!   size changes of each encoding and `content-type` / `cache-control`
+//!   changes. Exits with `0` if `pack`s are the same, `1` if there are
+//!   differences and `2` on error, so it can be used in CI.
+//! - `verify [OPTIONS] <INPUT_FILE_PATH>` validates `pack` structure and checks
+//!   that compressed versions decompress to the original content and that
+//!   `ETag`s match the content. Exits with error if any problem is found.
+//! - `sign [OPTIONS] <SIGNING_KEY_FILE_PATH> <INPUT_FILE_PATH>
+//!   <OUTPUT_FILE_PATH>` signs a `pack` with Ed25519 private key (PKCS#8 PEM,
+//!   eg. generated with `openssl genpkey -algorithm ed25519`), so loader can
+//!   verify it comes from a trusted source. Content of the `pack` is kept as
+//!   is, only the signature is appended (replacing previous one, if any).
+//!   Subcommands creating a `pack` can sign it directly with
+//!   `--signing-key-file-path`, `verify` checks the signature with
+//!   `--trusted-public-key-file-path`.
+//!
+//! A `pack` can also be served without writing any code (eg. in a container):
+//! - `serve [OPTIONS] <INPUT_FILE_PATH>` reads a `pack` and serves it with
+//!   built-in http server on `--bind` address (`127.0.0.1:8080` by default),
+//!   until `SIGTERM` or `ctrl+c` is received. Directory paths are resolved to
+//!   `--index-file-name` (`index.html` by default), paths not found can be
+//!   served with `--fallback-path` (eg. `/index.html` for single page
+//!   applications). With `--query-cache-buster v`, responses for versioned uris
+//!   (eg. `/app.js?v=hash`) are cached forever. `--access-log` prints a line
+//!   for each request. `--mmap` maps the `pack` instead of reading it, the file
+//!   must not be modified in place while served (replacing it, eg. by the
+//!   packer, is fine).
+//!
 //! ### Examples
 //! Let's say you have a `vcard-personal-portfolio` directory containing your
 //! web project (available in tests/data/ in repository). Directory structure
@@ -101,6 +163,11 @@
 //! [pack::store_file] or (mostly for test purposes) serialize to memory with
 //! [pack::store_memory].
 //!
+//! During development, instead of rebuilding a `pack` after each change, files
+//! can be served directly from a directory with [pack_fs::PackFs]. It
+//! implements the loader `Pack` trait, so the same `Responder` code is used as
+//! with a built `pack`.
+//!
 //! ### Examples
 //! This example will do exactly the same as one for application scenario:
 //! ```no_run
@@ -109,7 +176,7 @@
 //! # use web_static_pack_packer::{
 //! #     directory::{search, SearchOptions},
 //! #     file::BuildFromPathOptions,
-//! #     pack::{store_file, Builder},
+//! #     pack::{store_file, Builder, StoreOptions},
 //! # };
 //!
 //! # fn main() -> Result<(), Error> {
@@ -127,7 +194,11 @@
 //! let pack = pack.finalize();
 //!
 //! // store (serialize `pack` to the fs) to be included in the target app
-//! store_file(&pack, &PathBuf::from("vcard-personal-portfolio.pack"))?;
+//! store_file(
+//!     &pack,
+//!     &PathBuf::from("vcard-personal-portfolio.pack"),
+//!     &StoreOptions::default(),
+//! )?;
 //! # Ok(())
 //! # }
 //! ```
@@ -139,8 +210,14 @@
 
 pub use web_static_pack_common as common;
 
+pub mod diff;
 pub mod directory;
+pub mod extract;
 pub mod file;
 pub mod file_pack_path;
+pub mod inspect;
 pub mod pack;
+pub mod pack_fs;
+pub mod pack_index_fallback;
 pub mod pack_path;
+pub mod verify;
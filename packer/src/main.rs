@@ -3,10 +3,29 @@
 
 #![warn(missing_docs)]
 
-use anyhow::{Context, Error};
-use clap::{Args, Parser, Subcommand};
-use std::{io::stdin, path::PathBuf};
-use web_static_pack_packer::{directory, file, file_pack_path, pack};
+use anyhow::{Context, Error, anyhow, ensure};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use ed25519_dalek::{
+    SigningKey, VerifyingKey,
+    pkcs8::{DecodePrivateKey, DecodePublicKey},
+};
+use serde::Serialize;
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{Write, stdin, stdout},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+#[cfg(feature = "serve")]
+use std::{net::SocketAddr, sync::Arc};
+#[cfg(feature = "serve")]
+use tokio::{net::TcpListener, runtime::Runtime, signal};
+#[cfg(feature = "serve")]
+use web_static_pack_packer::pack_index_fallback;
+use web_static_pack_packer::{
+    diff, directory, extract, file, file_pack_path, inspect, pack, pack_path, verify,
+};
 
 #[derive(Parser, Debug)]
 #[command(version, about)]
@@ -42,6 +61,93 @@ impl FileGlobalOptions {
     }
 }
 
+#[derive(Args, Debug)]
+struct StoreGlobalOptions {
+    /// Store checksum of the `pack` in its header, so corruption can be
+    /// detected when loading. If not set, uses sane defaults.
+    #[arg(long)]
+    pub checksum: Option<bool>,
+    /// Sign `pack` with Ed25519 private key from this file (PKCS#8 PEM).
+    #[arg(long)]
+    pub signing_key_file_path: Option<PathBuf>,
+    /// When replacing existing `pack`, keep its permissions. If not set, uses
+    /// sane defaults.
+    #[arg(long)]
+    pub preserve_permissions: Option<bool>,
+}
+impl StoreGlobalOptions {
+    pub fn into_pack_store_options(self) -> Result<pack::StoreOptions, Error> {
+        let mut pack_store_options = pack::StoreOptions::default();
+
+        if let Some(checksum) = self.checksum {
+            pack_store_options.checksum = checksum;
+        }
+
+        if let Some(preserve_permissions) = self.preserve_permissions {
+            pack_store_options.preserve_permissions = preserve_permissions;
+        }
+
+        if let Some(signing_key_file_path) = self.signing_key_file_path {
+            pack_store_options.signing_key = Some(signing_key_read(&signing_key_file_path)?);
+        }
+
+        Ok(pack_store_options)
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum OutputFormat {
+    /// Human readable text.
+    Human,
+    /// Machine readable json.
+    Json,
+}
+
+#[derive(Args, Debug)]
+struct OutputGlobalOptions {
+    /// Output format.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+impl OutputGlobalOptions {
+    pub fn print(
+        &self,
+        value: &(impl Serialize + Display),
+    ) -> Result<(), Error> {
+        let mut stdout = stdout().lock();
+        match self.format {
+            OutputFormat::Human => {
+                write!(stdout, "{value}")?;
+            }
+            OutputFormat::Json => {
+                serde_json::to_writer_pretty(&mut stdout, value)?;
+                writeln!(stdout)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ConflictPolicy {
+    /// Fail if the same path exists in multiple input `pack`s.
+    Error,
+    /// Use file from the first input `pack` containing the path.
+    FirstWins,
+    /// Use file from the last input `pack` containing the path.
+    LastWins,
+}
+impl ConflictPolicy {
+    pub fn into_pack_conflict_policy(self) -> pack::ConflictPolicy {
+        match self {
+            ConflictPolicy::Error => pack::ConflictPolicy::Error,
+            ConflictPolicy::FirstWins => pack::ConflictPolicy::FirstWins,
+            ConflictPolicy::LastWins => pack::ConflictPolicy::LastWins,
+        }
+    }
+}
+
 #[derive(Subcommand, Debug)]
 enum Command {
     /// Creates a single `pack` from recursively searching through single
@@ -52,6 +158,8 @@ enum Command {
     DirectorySingle {
         #[command(flatten)]
         file_global_options: FileGlobalOptions,
+        #[command(flatten)]
+        store_global_options: StoreGlobalOptions,
 
         /// Whether to follow links while traversing directories. If not set,
         /// uses sane defaults.
@@ -69,6 +177,8 @@ enum Command {
     FilesCmd {
         #[command(flatten)]
         file_global_options: FileGlobalOptions,
+        #[command(flatten)]
+        store_global_options: StoreGlobalOptions,
 
         /// Output `pack` path.
         output_file_path: PathBuf,
@@ -85,6 +195,8 @@ enum Command {
     FilesStdin {
         #[command(flatten)]
         file_global_options: FileGlobalOptions,
+        #[command(flatten)]
+        store_global_options: StoreGlobalOptions,
 
         /// Base directory path, used to resolve relative for file inside
         /// `pack`. All added files must be inside this directory.
@@ -93,18 +205,350 @@ enum Command {
         /// Output `pack` path.
         output_file_path: PathBuf,
     },
+    /// Creates `pack` by combining files from multiple existing `pack`s.
+    ///
+    /// Files are copied as they are, compressed versions are not recalculated.
+    Merge {
+        #[command(flatten)]
+        store_global_options: StoreGlobalOptions,
+
+        /// How to handle the same path existing in multiple input `pack`s.
+        #[arg(long, value_enum, default_value_t = ConflictPolicy::Error)]
+        conflict_policy: ConflictPolicy,
+
+        /// Output `pack` path.
+        output_file_path: PathBuf,
+
+        /// List of `pack`s to be merged.
+        #[arg(required = true)]
+        input_file_paths: Vec<PathBuf>,
+    },
+    /// Creates `pack` containing only files from existing `pack` with paths
+    /// matching any of glob patterns (eg. `/assets/**` or `/*.html`).
+    ///
+    /// Files are copied as they are, compressed versions are not recalculated.
+    Filter {
+        #[command(flatten)]
+        store_global_options: StoreGlobalOptions,
+
+        /// Keep files not matching any of patterns instead.
+        #[arg(long)]
+        invert: bool,
+
+        /// Input `pack` path.
+        input_file_path: PathBuf,
+
+        /// Output `pack` path.
+        output_file_path: PathBuf,
+
+        /// List of glob patterns matched against paths inside `pack`.
+        #[arg(required = true)]
+        patterns: Vec<String>,
+    },
+    /// Splits existing `pack` into two, first containing files with paths
+    /// matching any of glob patterns (eg. `/assets/**` or `/*.html`), second
+    /// containing remaining files.
+    ///
+    /// Files are copied as they are, compressed versions are not recalculated.
+    Split {
+        #[command(flatten)]
+        store_global_options: StoreGlobalOptions,
+
+        /// Input `pack` path.
+        input_file_path: PathBuf,
+
+        /// Output `pack` path for files matching patterns.
+        output_matching_file_path: PathBuf,
+
+        /// Output `pack` path for remaining files.
+        output_rest_file_path: PathBuf,
+
+        /// List of glob patterns matched against paths inside `pack`.
+        #[arg(required = true)]
+        patterns: Vec<String>,
+    },
+    /// Lists files in existing `pack`, with their sizes (for each of
+    /// encodings), `content-type` and `cache-control`.
+    List {
+        #[command(flatten)]
+        output_global_options: OutputGlobalOptions,
+
+        /// Input `pack` path.
+        input_file_path: PathBuf,
+    },
+    /// Shows all metadata and headers of a single file in existing `pack`.
+    Show {
+        #[command(flatten)]
+        output_global_options: OutputGlobalOptions,
+
+        /// Input `pack` path.
+        input_file_path: PathBuf,
+
+        /// Path of the file inside `pack`, eg. `/index.html`.
+        path: String,
+    },
+    /// Shows sizes statistics of existing `pack`, including compression ratio
+    /// per mime type and the largest files.
+    Stats {
+        #[command(flatten)]
+        output_global_options: OutputGlobalOptions,
+
+        /// Number of the largest files to show.
+        #[arg(long, default_value_t = 10)]
+        largest_files_count: usize,
+
+        /// Input `pack` path.
+        input_file_path: PathBuf,
+    },
+    /// Writes files from existing `pack` into directory, mirroring paths
+    /// inside `pack`.
+    ///
+    /// Existing files are never overwritten.
+    Extract {
+        /// Also write `gzip` and `brotli` versions of files (if present)
+        /// alongside, with `.gz` and `.br` suffix added.
+        #[arg(long)]
+        write_compressed: bool,
+
+        /// Write json manifest with metadata and headers of all files to this
+        /// path.
+        #[arg(long)]
+        manifest_file_path: Option<PathBuf>,
+
+        /// Input `pack` path.
+        input_file_path: PathBuf,
+
+        /// The directory to write files to.
+        output_directory_path: PathBuf,
+    },
+    /// Compares two `pack`s, showing added, removed and changed files.
+    ///
+    /// Exits with `0` if `pack`s are the same, `1` if there are differences
+    /// and `2` on error.
+    Diff {
+        #[command(flatten)]
+        output_global_options: OutputGlobalOptions,
+
+        /// Old `pack` path.
+        old_file_path: PathBuf,
+
+        /// New `pack` path.
+        new_file_path: PathBuf,
+    },
+    /// Verifies existing `pack`: validates its structure, checks if compressed
+    /// versions of files match the original content and if `ETag`s match the
+    /// content.
+    ///
+    /// Exits with error if any problem is found.
+    Verify {
+        #[command(flatten)]
+        output_global_options: OutputGlobalOptions,
+
+        /// Require `pack` to be signed with private key matching public key
+        /// (PKCS#8 PEM) from this file. Can be specified multiple times.
+        #[arg(long)]
+        trusted_public_key_file_path: Vec<PathBuf>,
+
+        /// Input `pack` path.
+        input_file_path: PathBuf,
+    },
+    /// Serves existing `pack` with built-in http server, until terminated with
+    /// `SIGTERM` or `ctrl+c`.
+    ///
+    /// `pack` is read into memory on start (or mapped with `--mmap`), later
+    /// changes of the file are not picked up.
+    #[cfg(feature = "serve")]
+    Serve {
+        /// Address to listen on. Use `0.0.0.0:8080` to accept connections from
+        /// other hosts (eg. when running in container).
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        bind: SocketAddr,
+
+        /// File name served for directory paths (ending with `/`). If not set,
+        /// uses sane defaults (`index.html`). Empty value disables index
+        /// files.
+        #[arg(long)]
+        index_file_name: Option<String>,
+
+        /// Path of file served for paths not found in `pack`, eg.
+        /// `/index.html` for single page applications.
+        #[arg(long)]
+        fallback_path: Option<String>,
+
+        /// Name of query parameter marking versioned uris, eg. `v` for
+        /// `/app.js?v=hash`. Responses for such uris are cached forever,
+        /// regardless of file settings.
+        #[arg(long)]
+        query_cache_buster: Option<String>,
+
+        /// Print access log line for each request to stdout.
+        #[arg(long)]
+        access_log: bool,
+
+        /// Maximum number of connections served at the same time. If not set,
+        /// uses sane defaults.
+        #[arg(long)]
+        connections_max: Option<usize>,
+
+        /// Map `pack` into memory instead of reading it, so memory is shared
+        /// with page cache and other processes mapping the same file.
+        ///
+        /// File must not be modified or truncated in place while being served,
+        /// otherwise server may crash or serve corrupted content. Replacing it
+        /// (eg. by packer, which writes new file and renames it, or with `mv`)
+        /// is fine.
+        #[arg(long)]
+        mmap: bool,
+
+        /// Input `pack` path.
+        input_file_path: PathBuf,
+    },
+    /// Signs existing `pack` with Ed25519 private key, so it can be verified by
+    /// loader against trusted public keys.
+    ///
+    /// Keys can be generated with `openssl genpkey -algorithm ed25519 -out
+    /// signing-key.pem` and `openssl pkey -in signing-key.pem -pubout -out
+    /// public-key.pem`.
+    Sign {
+        /// When replacing existing `pack`, keep its permissions. If not set,
+        /// uses sane defaults.
+        #[arg(long)]
+        preserve_permissions: Option<bool>,
+
+        /// Private key (PKCS#8 PEM) path.
+        signing_key_file_path: PathBuf,
+
+        /// Input `pack` path.
+        input_file_path: PathBuf,
+
+        /// Output `pack` path.
+        output_file_path: PathBuf,
+    },
+}
+
+/// Reads Ed25519 private key from PKCS#8 PEM file.
+fn signing_key_read(path: &Path) -> Result<SigningKey, Error> {
+    let signing_key = SigningKey::read_pkcs8_pem_file(path)
+        .with_context(|| path.to_string_lossy().into_owned())?;
+    Ok(signing_key)
+}
+/// Reads Ed25519 public key from PKCS#8 PEM file.
+fn verifying_key_read(path: &Path) -> Result<VerifyingKey, Error> {
+    let verifying_key = VerifyingKey::read_public_key_pem_file(path)
+        .with_context(|| path.to_string_lossy().into_owned())?;
+    Ok(verifying_key)
+}
+
+/// Runs [Command::Diff], returns `true` if `pack`s differ.
+fn diff_run(
+    output_global_options: &OutputGlobalOptions,
+    old_file_path: &Path,
+    new_file_path: &Path,
+) -> Result<bool, Error> {
+    let pack_old = pack::load_file(old_file_path)
+        .with_context(|| old_file_path.to_string_lossy().into_owned())?;
+    let pack_new = pack::load_file(new_file_path)
+        .with_context(|| new_file_path.to_string_lossy().into_owned())?;
+
+    let diff = diff::diff(&pack_old, &pack_new);
+    output_global_options.print(&diff)?;
+
+    Ok(!diff.is_empty())
+}
+
+/// Runs [Command::Serve] until shutdown signal is received.
+#[cfg(feature = "serve")]
+async fn serve_run<P>(
+    pack: P,
+    bind: SocketAddr,
+    pack_index_fallback_options: pack_index_fallback::PackIndexFallbackOptions,
+    responder_options: web_static_pack::responder::ResponderOptions,
+    access_log: bool,
+    serve_options: &web_static_pack::server::ServeOptions,
+) -> Result<(), Error>
+where
+    P: web_static_pack::pack::Pack + Send + Sync + 'static,
+{
+    let pack = pack_index_fallback::PackIndexFallback::new(pack, pack_index_fallback_options);
+
+    let responder = web_static_pack::responder::ResponderOwned::new_with_options(
+        Arc::new(pack),
+        Arc::new(responder_options),
+    );
+
+    let listener = TcpListener::bind(bind)
+        .await
+        .with_context(|| bind.to_string())?;
+    eprintln!("Serving on http://{}", listener.local_addr()?);
+
+    web_static_pack::server::serve_listener_with(
+        listener,
+        move |request, remote_address| {
+            let response = responder.respond_request_flatten(&request);
+
+            if access_log {
+                println!(
+                    "{} \"{} {} {:?}\" {} {}",
+                    remote_address,
+                    request.method(),
+                    request
+                        .uri()
+                        .path_and_query()
+                        .map_or("/", |path_and_query| path_and_query.as_str()),
+                    request.version(),
+                    response.status().as_u16(),
+                    response.body().data().len(),
+                );
+            }
+
+            response
+        },
+        shutdown_signal(),
+        serve_options,
+    )
+    .await;
+
+    eprintln!("Server stopped");
+
+    Ok(())
+}
+
+/// Completes when `SIGTERM` (on unix) or `ctrl+c` is received.
+#[cfg(feature = "serve")]
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c().await.unwrap();
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .unwrap()
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {},
+        () = terminate => {},
+    }
 }
 
-fn main() -> Result<(), Error> {
+fn main() -> Result<ExitCode, Error> {
     let arguments = Arguments::parse();
 
     match arguments.command {
         Command::DirectorySingle {
+            store_global_options,
             file_global_options,
             follow_links,
             input_directory_path,
             output_file_path,
         } => {
+            let store_options = store_global_options.into_pack_store_options()?;
+
             let mut directory_search_options = directory::SearchOptions::default();
             if let Some(follow_links) = follow_links {
                 directory_search_options.follow_links = follow_links;
@@ -124,14 +568,17 @@ fn main() -> Result<(), Error> {
             }
 
             let pack = pack_builder.finalize();
-            pack::store_file(&pack, &output_file_path)?;
+            pack::store_file(&pack, &output_file_path, &store_options)?;
         }
         Command::FilesCmd {
+            store_global_options,
             file_global_options,
             output_file_path,
             input_base_directory_path,
             input_file_paths,
         } => {
+            let store_options = store_global_options.into_pack_store_options()?;
+
             let file_build_from_path_options =
                 file_global_options.into_file_build_from_path_options();
 
@@ -153,13 +600,16 @@ fn main() -> Result<(), Error> {
             }
 
             let pack = pack_builder.finalize();
-            pack::store_file(&pack, &output_file_path)?;
+            pack::store_file(&pack, &output_file_path, &store_options)?;
         }
         Command::FilesStdin {
+            store_global_options,
             file_global_options,
             input_base_directory_path,
             output_file_path,
         } => {
+            let store_options = store_global_options.into_pack_store_options()?;
+
             let file_build_from_path_options =
                 file_global_options.into_file_build_from_path_options();
 
@@ -183,9 +633,255 @@ fn main() -> Result<(), Error> {
             }
 
             let pack = pack_builder.finalize();
-            pack::store_file(&pack, &output_file_path)?;
+            pack::store_file(&pack, &output_file_path, &store_options)?;
+        }
+        Command::Merge {
+            store_global_options,
+            conflict_policy,
+            output_file_path,
+            input_file_paths,
+        } => {
+            let store_options = store_global_options.into_pack_store_options()?;
+
+            let conflict_policy = conflict_policy.into_pack_conflict_policy();
+
+            let mut pack_builder = pack::Builder::new();
+            for input_file_path in input_file_paths {
+                // TODO: move this into try block with shared context
+                let input_file_error_context = || input_file_path.to_string_lossy().into_owned();
+
+                let pack =
+                    pack::load_file(&input_file_path).with_context(input_file_error_context)?;
+
+                pack_builder
+                    .pack_add(pack, conflict_policy)
+                    .with_context(input_file_error_context)?;
+            }
+
+            let pack = pack_builder.finalize();
+            pack::store_file(&pack, &output_file_path, &store_options)?;
+        }
+        Command::Filter {
+            store_global_options,
+            invert,
+            input_file_path,
+            output_file_path,
+            patterns,
+        } => {
+            let store_options = store_global_options.into_pack_store_options()?;
+
+            let glob_set = pack_path::glob_set_build(patterns.iter().map(String::as_str))?;
+
+            let pack = pack::load_file(&input_file_path)
+                .with_context(|| input_file_path.to_string_lossy().into_owned())?;
+
+            let (pack_matching, pack_rest) =
+                pack::split(pack, |pack_path| glob_set.is_match(&**pack_path));
+            let pack = if !invert { pack_matching } else { pack_rest };
+
+            pack::store_file(&pack, &output_file_path, &store_options)?;
+        }
+        Command::Split {
+            store_global_options,
+            input_file_path,
+            output_matching_file_path,
+            output_rest_file_path,
+            patterns,
+        } => {
+            let store_options = store_global_options.into_pack_store_options()?;
+
+            let glob_set = pack_path::glob_set_build(patterns.iter().map(String::as_str))?;
+
+            let pack = pack::load_file(&input_file_path)
+                .with_context(|| input_file_path.to_string_lossy().into_owned())?;
+
+            let (pack_matching, pack_rest) =
+                pack::split(pack, |pack_path| glob_set.is_match(&**pack_path));
+
+            pack::store_file(&pack_matching, &output_matching_file_path, &store_options)?;
+            pack::store_file(&pack_rest, &output_rest_file_path, &store_options)?;
+        }
+        Command::List {
+            output_global_options,
+            input_file_path,
+        } => {
+            let pack = pack::load_file(&input_file_path)
+                .with_context(|| input_file_path.to_string_lossy().into_owned())?;
+
+            output_global_options.print(&inspect::list(&pack))?;
+        }
+        Command::Show {
+            output_global_options,
+            input_file_path,
+            path,
+        } => {
+            let pack = pack::load_file(&input_file_path)
+                .with_context(|| input_file_path.to_string_lossy().into_owned())?;
+
+            let show =
+                inspect::show(&pack, &path).ok_or_else(|| anyhow!("path {path} not found"))?;
+
+            output_global_options.print(&show)?;
+        }
+        Command::Stats {
+            output_global_options,
+            largest_files_count,
+            input_file_path,
+        } => {
+            let pack = pack::load_file(&input_file_path)
+                .with_context(|| input_file_path.to_string_lossy().into_owned())?;
+
+            output_global_options.print(&inspect::stats(&pack, largest_files_count))?;
+        }
+        Command::Extract {
+            write_compressed,
+            manifest_file_path,
+            input_file_path,
+            output_directory_path,
+        } => {
+            let pack = pack::load_file(&input_file_path)
+                .with_context(|| input_file_path.to_string_lossy().into_owned())?;
+
+            let manifest = extract::extract(
+                &pack,
+                &output_directory_path,
+                &extract::ExtractOptions { write_compressed },
+            )?;
+
+            if let Some(manifest_file_path) = manifest_file_path {
+                let manifest_file = File::create(&manifest_file_path)
+                    .with_context(|| manifest_file_path.to_string_lossy().into_owned())?;
+                serde_json::to_writer_pretty(manifest_file, &manifest)?;
+            }
+        }
+        Command::Diff {
+            output_global_options,
+            old_file_path,
+            new_file_path,
+        } => {
+            // exit codes follow diff(1) convention, errors are reported as 2
+            // to be distinguishable from differences
+            match diff_run(&output_global_options, &old_file_path, &new_file_path) {
+                Ok(false) => {}
+                Ok(true) => return Ok(ExitCode::from(1)),
+                Err(error) => {
+                    eprintln!("Error: {error:?}");
+                    return Ok(ExitCode::from(2));
+                }
+            }
+        }
+        Command::Verify {
+            output_global_options,
+            trusted_public_key_file_path,
+            input_file_path,
+        } => {
+            let trusted_keys = trusted_public_key_file_path
+                .iter()
+                .map(|path| verifying_key_read(path))
+                .collect::<Result<Box<[_]>, Error>>()?;
+
+            let pack = if trusted_keys.is_empty() {
+                pack::load_file(&input_file_path)
+            } else {
+                pack::load_file_signed(&input_file_path, &trusted_keys)
+            }
+            .with_context(|| input_file_path.to_string_lossy().into_owned())?;
+
+            let verify = verify::verify(&pack);
+            output_global_options.print(&verify)?;
+
+            ensure!(verify.is_valid(), "pack verification failed");
+        }
+        #[cfg(feature = "serve")]
+        Command::Serve {
+            bind,
+            index_file_name,
+            fallback_path,
+            query_cache_buster,
+            access_log,
+            connections_max,
+            mmap,
+            input_file_path,
+        } => {
+            let mut pack_index_fallback_options =
+                pack_index_fallback::PackIndexFallbackOptions::default();
+            if let Some(index_file_name) = index_file_name {
+                pack_index_fallback_options.index_file_name =
+                    (!index_file_name.is_empty()).then_some(index_file_name);
+            }
+            pack_index_fallback_options.fallback_path = fallback_path;
+
+            let mut responder_options = web_static_pack::responder::ResponderOptions::default();
+            if let Some(parameter_name) = query_cache_buster {
+                responder_options.query_policy =
+                    web_static_pack::responder::QueryPolicy::CacheBuster { parameter_name };
+            }
+
+            let mut serve_options = web_static_pack::server::ServeOptions::default();
+            if let Some(connections_max) = connections_max {
+                serve_options.connections_max = Some(connections_max);
+            }
+
+            let load_checked_options = web_static_pack::loader::LoadCheckedOptions::default();
+            let runtime = Runtime::new()?;
+            if mmap {
+                // SAFETY: user promises not to modify the file in place, see
+                // `--mmap` description
+                let pack = unsafe {
+                    web_static_pack::pack_owned::PackOwned::map_file(
+                        &input_file_path,
+                        &load_checked_options,
+                    )
+                }
+                .with_context(|| input_file_path.to_string_lossy().into_owned())?;
+
+                runtime.block_on(serve_run(
+                    pack,
+                    bind,
+                    pack_index_fallback_options,
+                    responder_options,
+                    access_log,
+                    &serve_options,
+                ))?;
+            } else {
+                let pack = web_static_pack::pack_owned::PackOwned::read_file(
+                    &input_file_path,
+                    &load_checked_options,
+                )
+                .with_context(|| input_file_path.to_string_lossy().into_owned())?;
+
+                runtime.block_on(serve_run(
+                    pack,
+                    bind,
+                    pack_index_fallback_options,
+                    responder_options,
+                    access_log,
+                    &serve_options,
+                ))?;
+            }
+        }
+        Command::Sign {
+            preserve_permissions,
+            signing_key_file_path,
+            input_file_path,
+            output_file_path,
+        } => {
+            let signing_key = signing_key_read(&signing_key_file_path)?;
+
+            let mut sign_options = pack::SignOptions::default();
+            if let Some(preserve_permissions) = preserve_permissions {
+                sign_options.preserve_permissions = preserve_permissions;
+            }
+
+            pack::sign_file(
+                &input_file_path,
+                &output_file_path,
+                &signing_key,
+                &sign_options,
+            )
+            .with_context(|| input_file_path.to_string_lossy().into_owned())?;
         }
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
@@ -1,16 +1,38 @@
-//! Pack helpers. Contains [Builder], builder for [Pack].
+//! Pack helpers. Contains [Builder], builder for [Pack], functions to store and
+//! load [Pack] and helpers to [split] existing [Pack].
 
 use crate::{
-    common::{PACK_FILE_MAGIC, PACK_FILE_VERSION, file::File, pack::Pack, pack_path::PackPath},
+    common::{
+        PACK_FILE_CHECKSUM_ALGORITHM_BLAKE3, PACK_FILE_CHECKSUM_ALGORITHM_NONE,
+        PACK_FILE_HEADER_LENGTH, PACK_FILE_MAGIC, PACK_FILE_SIGNATURE_MAGIC, PACK_FILE_VERSION,
+        file::File, pack::Pack, pack_path::PackPath,
+    },
     file_pack_path::FilePackPath,
 };
-use anyhow::{Error, bail};
-use rkyv::{api::high::to_bytes_in, rancor, ser::writer::IoWriter, util::AlignedVec};
+use anyhow::{Context, Error, bail};
+use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
+use rkyv::{deserialize, rancor, to_bytes, util::AlignedVec};
 use std::{
     collections::{HashMap, hash_map},
+    ffi::{OsStr, OsString},
     fs, io,
-    path::Path,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicU64, Ordering},
 };
+use web_static_pack::loader;
+
+/// Policy for resolving conflicts, when file being added to [Builder] has the
+/// same [PackPath] as file already present.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ConflictPolicy {
+    /// Fail with an error.
+    Error,
+    /// Keep file already present in [Builder], ignore file being added.
+    FirstWins,
+    /// Replace file already present in [Builder] with file being added.
+    LastWins,
+}
 
 /// Main builder for `pack`. Inside it keeps list of [File] under respective
 /// [PackPath].
@@ -26,20 +48,37 @@ impl Builder {
         Self { files_by_pack_path }
     }
 
-    /// Adds file to the `pack`.
+    /// Adds file to the `pack`. Fails if file with the same path was already
+    /// added.
     pub fn file_pack_path_add(
         &mut self,
         file_pack_path: FilePackPath,
     ) -> Result<(), Error> {
-        let entry = match self.files_by_pack_path.entry(file_pack_path.pack_path) {
-            hash_map::Entry::Occupied(_entry) => {
-                bail!("file on specified path already exist");
+        self.file_pack_path_add_with_conflict_policy(file_pack_path, ConflictPolicy::Error)
+    }
+
+    /// Adds file to the `pack`, resolving path conflicts with given
+    /// [ConflictPolicy].
+    pub fn file_pack_path_add_with_conflict_policy(
+        &mut self,
+        file_pack_path: FilePackPath,
+        conflict_policy: ConflictPolicy,
+    ) -> Result<(), Error> {
+        match self.files_by_pack_path.entry(file_pack_path.pack_path) {
+            hash_map::Entry::Occupied(mut entry) => match conflict_policy {
+                ConflictPolicy::Error => {
+                    bail!("file on specified path already exist");
+                }
+                ConflictPolicy::FirstWins => {}
+                ConflictPolicy::LastWins => {
+                    entry.insert(file_pack_path.file);
+                }
+            },
+            hash_map::Entry::Vacant(entry) => {
+                entry.insert(file_pack_path.file);
             }
-            hash_map::Entry::Vacant(entry) => entry,
         };
 
-        entry.insert(file_pack_path.file);
-
         Ok(())
     }
 
@@ -55,6 +94,31 @@ impl Builder {
         Ok(())
     }
 
+    /// Adds all files from existing `pack` (eg. loaded with [load_file]),
+    /// resolving path conflicts with given [ConflictPolicy].
+    ///
+    /// Files are moved as they are, their compressed versions are not
+    /// recalculated.
+    pub fn pack_add(
+        &mut self,
+        pack: Pack,
+        conflict_policy: ConflictPolicy,
+    ) -> Result<(), Error> {
+        pack.files_by_path
+            .into_iter()
+            .try_for_each(|(pack_path, file)| {
+                let pack_path_error_context = pack_path.to_string();
+
+                self.file_pack_path_add_with_conflict_policy(
+                    FilePackPath { file, pack_path },
+                    conflict_policy,
+                )
+                .context(pack_path_error_context)
+            })?;
+
+        Ok(())
+    }
+
     /// Finalizes to builder, returning built [Pack].
     pub fn finalize(self) -> Pack {
         Pack {
@@ -63,39 +127,663 @@ impl Builder {
     }
 }
 
+/// Options for [store_memory] and [store_file].
+///
+/// If not sure what to set here, use [Default].
+#[derive(Debug)]
+pub struct StoreOptions {
+    /// Calculate BLAKE3 checksum of serialized `pack` and store it in the
+    /// header, so corruption can be detected by loader.
+    pub checksum: bool,
+    /// Sign `pack` with this key, appending signature trailer. Signed `pack`s
+    /// can be verified by loader against a set of trusted public keys.
+    pub signing_key: Option<SigningKey>,
+
+    /// When replacing existing file in [store_file], copy its permissions to
+    /// the new file. Otherwise new file gets default permissions.
+    pub preserve_permissions: bool,
+}
+impl Default for StoreOptions {
+    fn default() -> Self {
+        Self {
+            checksum: true,
+            signing_key: None,
+            preserve_permissions: true,
+        }
+    }
+}
+
 fn store(
     pack: &Pack,
+    options: &StoreOptions,
+    mut writer: impl io::Write,
+) -> Result<(), Error> {
+    // header length and checksum must be known upfront, so pack is serialized
+    // to memory first
+    let payload = to_bytes::<rancor::Error>(pack)?;
+
+    let (checksum_algorithm, checksum) = if options.checksum {
+        (
+            PACK_FILE_CHECKSUM_ALGORITHM_BLAKE3,
+            *blake3::hash(&payload).as_bytes(),
+        )
+    } else {
+        (PACK_FILE_CHECKSUM_ALGORITHM_NONE, [0u8; 32])
+    };
+
+    // NOTE: we rely on `pack` being 16-aligned, header length
+    // (PACK_FILE_HEADER_LENGTH) is multiple of 16 so it keeps alignment
+    // unchanged, but changing it may break it.
+    let mut header = Vec::with_capacity(PACK_FILE_HEADER_LENGTH);
+    header.extend_from_slice(&PACK_FILE_MAGIC.to_ne_bytes());
+    header.extend_from_slice(&PACK_FILE_VERSION.to_ne_bytes());
+    header.extend_from_slice(&checksum_algorithm.to_ne_bytes());
+    header.extend_from_slice(&(payload.len() as u64).to_ne_bytes());
+    header.extend_from_slice(&checksum);
+    assert!(header.len() == PACK_FILE_HEADER_LENGTH);
+
+    writer.write_all(&header)?;
+    writer.write_all(&payload)?;
+
+    // signature covers both header and payload
+    if let Some(signing_key) = &options.signing_key {
+        let mut header_payload_hasher = blake3::Hasher::new();
+        header_payload_hasher.update(&header);
+        header_payload_hasher.update(&payload);
+
+        signature_trailer_write(signing_key, &header_payload_hasher.finalize(), writer)?;
+    }
+
+    Ok(())
+}
+
+/// Writes signature trailer (see [crate::common::PACK_FILE_SIGNATURE_MAGIC]),
+/// signing `header_payload_hash`, BLAKE3 hash of header and payload.
+fn signature_trailer_write(
+    signing_key: &SigningKey,
+    header_payload_hash: &blake3::Hash,
     mut writer: impl io::Write,
 ) -> Result<(), Error> {
-    // NOTE: we rely on `pack` being 16-aligned two u64 at the beginning keeps
-    // alignment unchanged, but adding anything here will break it.
-    writer.write_all(&PACK_FILE_MAGIC.to_ne_bytes())?;
-    writer.write_all(&PACK_FILE_VERSION.to_ne_bytes())?;
-    to_bytes_in::<_, rancor::Error>(pack, IoWriter::new(writer))?;
+    let signature = signing_key.sign(header_payload_hash.as_bytes());
+
+    writer.write_all(&PACK_FILE_SIGNATURE_MAGIC.to_ne_bytes())?;
+    writer.write_all(signing_key.verifying_key().as_bytes())?;
+    writer.write_all(&signature.to_bytes())?;
 
     Ok(())
 }
 
 /// Serializes `pack` to [AlignedVec]. Serialized data can be used with `load`
 /// method of loader.
-pub fn store_memory(pack: &Pack) -> Result<AlignedVec, Error> {
+pub fn store_memory(
+    pack: &Pack,
+    options: &StoreOptions,
+) -> Result<AlignedVec, Error> {
     let mut buffer = AlignedVec::new();
-    store(pack, &mut buffer)?;
+    store(pack, options, &mut buffer)?;
     Ok(buffer)
 }
 
 /// Serializes `pack` to given file path Serialized data can be used with `load`
 /// method of loader.
+///
+/// File is replaced atomically: content is written to a temporary file in the
+/// same directory, synced and then renamed to `path`. Readers (eg. ones having
+/// previous version mmap'd) will never observe partially written file.
+///
+/// Temporary file name is unique for each call, so concurrent calls (also from
+/// different processes) do not interfere. Temporary files left by crashed
+/// processes are skipped, not removed.
 pub fn store_file(
     pack: &Pack,
     path: &Path,
+    options: &StoreOptions,
+) -> Result<(), Error> {
+    file_write(path, options.preserve_permissions, |file| {
+        store(pack, options, file)
+    })
+}
+
+/// Signs existing serialized `pack` with `signing_key`, see
+/// [StoreOptions::signing_key].
+///
+/// `serialized` is validated with [loader::load_checked] and kept as is (eg.
+/// checksum settings are not changed), only the signature trailer is appended.
+/// If `pack` was already signed, previous signature is replaced.
+pub fn sign_memory(
+    serialized: &AlignedVec,
+    signing_key: &SigningKey,
+) -> Result<AlignedVec, Error> {
+    let mut buffer = AlignedVec::new();
+    sign(serialized, signing_key, &mut buffer)?;
+    Ok(buffer)
+}
+
+/// Options for [sign_file].
+///
+/// If not sure what to set here, use [Default].
+#[derive(Debug)]
+pub struct SignOptions {
+    /// When replacing existing file, copy its permissions to the new file.
+    /// Otherwise new file gets default permissions.
+    pub preserve_permissions: bool,
+}
+impl Default for SignOptions {
+    fn default() -> Self {
+        Self {
+            preserve_permissions: true,
+        }
+    }
+}
+
+/// Signs `pack` stored in `input_path` with `signing_key` and writes it to
+/// `output_path`. See [sign_memory] for details.
+///
+/// `output_path` is replaced atomically, like in [store_file], so it may be
+/// the same as `input_path`.
+pub fn sign_file(
+    input_path: &Path,
+    output_path: &Path,
+    signing_key: &SigningKey,
+    options: &SignOptions,
+) -> Result<(), Error> {
+    let serialized = file_read(input_path)?;
+
+    file_write(output_path, options.preserve_permissions, |file| {
+        sign(&serialized, signing_key, file)
+    })
+}
+
+fn sign(
+    serialized: &AlignedVec,
+    signing_key: &SigningKey,
+    mut writer: impl io::Write,
+) -> Result<(), Error> {
+    loader::load_checked(serialized, &loader::LoadCheckedOptions::default())?;
+
+    // header and payload are validated above, anything after them is existing
+    // signature trailer, that will be replaced
+    let payload_length = u64::from_ne_bytes(serialized[24..32].try_into().unwrap());
+    let header_payload = &serialized[..PACK_FILE_HEADER_LENGTH + payload_length as usize];
+
+    writer.write_all(header_payload)?;
+    signature_trailer_write(signing_key, &blake3::hash(header_payload), writer)?;
+
+    Ok(())
+}
+
+/// Atomically replaces file at `path` with content written by `write`.
+///
+/// Content is written to a temporary file in the same directory, synced and
+/// then renamed to `path`. If `preserve_permissions` is set, permissions of
+/// replaced file are copied to the new one.
+fn file_write(
+    path: &Path,
+    preserve_permissions: bool,
+    write: impl FnOnce(&mut fs::File) -> Result<(), Error>,
 ) -> Result<(), Error> {
-    let mut file = fs::File::create(path)?;
+    let file_name = path.file_name().context("path must point to a file")?;
+    let directory_path = match path.parent() {
+        Some(directory_path) if !directory_path.as_os_str().is_empty() => directory_path,
+        _ => Path::new("."),
+    };
 
-    store(pack, &mut file)?;
+    let permissions = if preserve_permissions {
+        match fs::metadata(path) {
+            Ok(metadata) => Some(metadata.permissions()),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+            Err(error) => return Err(error.into()),
+        }
+    } else {
+        None
+    };
 
-    file.sync_all()?;
-    drop(file);
+    let (mut file, temporary_path) = temporary_file_create(directory_path, file_name)?;
+
+    let result = (|| -> Result<(), Error> {
+        write(&mut file)?;
+
+        if let Some(permissions) = permissions {
+            file.set_permissions(permissions)?;
+        }
+
+        file.sync_all()?;
+        drop(file);
+
+        fs::rename(&temporary_path, path)?;
+
+        Ok(())
+    })();
+    if result.is_err() {
+        // temporary file was created by this call, so nobody else uses it
+        let _ = fs::remove_file(&temporary_path);
+    }
+    result?;
+
+    // make the rename durable
+    #[cfg(unix)]
+    fs::File::open(directory_path)?.sync_all()?;
 
     Ok(())
 }
+
+/// Creates new temporary file for [file_write] in `directory_path`, named after
+/// `file_name`, process id and a per process counter. Names already taken (eg.
+/// by other process with the same id, common in containers) are skipped.
+fn temporary_file_create(
+    directory_path: &Path,
+    file_name: &OsStr,
+) -> Result<(fs::File, PathBuf), Error> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    const ATTEMPTS_MAX: usize = 64;
+    for _ in 0..ATTEMPTS_MAX {
+        let mut temporary_file_name = OsString::from(".");
+        temporary_file_name.push(file_name);
+        temporary_file_name.push(format!(
+            ".{}.{}.tmp",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let temporary_path = directory_path.join(temporary_file_name);
+
+        match fs::File::create_new(&temporary_path) {
+            Ok(file) => return Ok((file, temporary_path)),
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error.into()),
+        }
+    }
+
+    bail!("unable to create unique temporary file in {directory_path:?}");
+}
+
+/// Loads [Pack] from serialized bytes, eg. created with [store_memory].
+///
+/// Contrary to loader (which maps content into archived representation), this
+/// deserializes (copies) all files into [Pack], so it can be modified, ex.
+/// added to [Builder] with [Builder::pack_add].
+///
+/// `serialized` is validated with [loader::load_checked] (including checksum,
+/// if present), so invalid or corrupted `pack`s are reported as errors.
+pub fn load_memory(serialized: &AlignedVec) -> Result<Pack, Error> {
+    let pack_archived = loader::load_checked(
+        serialized,
+        &loader::LoadCheckedOptions {
+            verify_checksum: true,
+            checksum_required: false,
+            verify_content_compressed: false,
+            verify_etag: false,
+        },
+    )?;
+    let pack = deserialize::<Pack, rancor::Error>(pack_archived)?;
+
+    Ok(pack)
+}
+
+/// Like [load_memory], but additionally requires `pack` to be signed by one
+/// of `trusted_keys`. See [loader::load_signed] for details.
+pub fn load_memory_signed(
+    serialized: &AlignedVec,
+    trusted_keys: &[VerifyingKey],
+) -> Result<Pack, Error> {
+    let pack_archived = loader::load_signed(
+        serialized,
+        trusted_keys,
+        &loader::LoadCheckedOptions {
+            verify_checksum: true,
+            checksum_required: false,
+            verify_content_compressed: false,
+            verify_etag: false,
+        },
+    )?;
+    let pack = deserialize::<Pack, rancor::Error>(pack_archived)?;
+
+    Ok(pack)
+}
+
+/// Loads [Pack] from given file path, eg. created with [store_file]. See
+/// [load_memory] for details.
+pub fn load_file(path: &Path) -> Result<Pack, Error> {
+    let serialized = file_read(path)?;
+
+    let pack = load_memory(&serialized)?;
+
+    Ok(pack)
+}
+
+/// Like [load_file], but additionally requires `pack` to be signed by one of
+/// `trusted_keys`. See [load_memory_signed] for details.
+pub fn load_file_signed(
+    path: &Path,
+    trusted_keys: &[VerifyingKey],
+) -> Result<Pack, Error> {
+    let serialized = file_read(path)?;
+
+    let pack = load_memory_signed(&serialized, trusted_keys)?;
+
+    Ok(pack)
+}
+
+fn file_read(path: &Path) -> Result<AlignedVec, Error> {
+    let mut file = fs::File::open(path)?;
+
+    // loader requires content to be aligned
+    let mut serialized = AlignedVec::new();
+    io::copy(&mut file, &mut serialized)?;
+    drop(file);
+
+    Ok(serialized)
+}
+
+/// Splits `pack` into two, first containing files for which `predicate`
+/// returned `true`, second containing all remaining files.
+///
+/// Files are moved as they are, their compressed versions are not
+/// recalculated.
+///
+/// # Examples
+///
+/// ```
+/// # use anyhow::Error;
+/// # use web_static_pack_packer::{
+/// #     common::pack_path::PackPath,
+/// #     file::{build_from_content, BuildFromContentOptions},
+/// #     file_pack_path::FilePackPath,
+/// #     pack::{split, Builder},
+/// # };
+/// #
+/// # fn main() -> Result<(), Error> {
+/// #
+/// let mut pack = Builder::new();
+/// for path in ["/index.html", "/assets/style.css"] {
+///     pack.file_pack_path_add(FilePackPath {
+///         file: build_from_content(
+///             Box::new(*b"content"),
+///             "text/plain; charset=utf-8".to_owned(),
+///             &BuildFromContentOptions::default(),
+///         ),
+///         pack_path: PackPath::from_string(path.to_owned()),
+///     })?;
+/// }
+/// let pack = pack.finalize();
+///
+/// let (pack_assets, pack_rest) = split(pack, |pack_path| pack_path.starts_with("/assets/"));
+/// assert!(pack_assets.files_by_path.contains_key("/assets/style.css"));
+/// assert!(pack_rest.files_by_path.contains_key("/index.html"));
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn split(
+    pack: Pack,
+    mut predicate: impl FnMut(&PackPath) -> bool,
+) -> (Pack, Pack) {
+    let (files_by_path_matching, files_by_path_rest) = pack
+        .files_by_path
+        .into_iter()
+        .partition::<HashMap<_, _>, _>(|(pack_path, _file)| predicate(pack_path));
+
+    (
+        Pack {
+            files_by_path: files_by_path_matching,
+        },
+        Pack {
+            files_by_path: files_by_path_rest,
+        },
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        Builder, ConflictPolicy, StoreOptions, load_file, load_memory, load_memory_signed,
+        sign_memory, store_file, store_memory,
+    };
+    use crate::{
+        common::pack_path::PackPath,
+        file::{BuildFromContentOptions, build_from_content},
+        file_pack_path::FilePackPath,
+    };
+    use ed25519_dalek::SigningKey;
+    use std::{env, fs, process, thread};
+    use test_case::test_case;
+
+    fn file_pack_path(content: &'static [u8]) -> FilePackPath {
+        FilePackPath {
+            file: build_from_content(
+                Box::from(content),
+                "text/plain; charset=utf-8".to_owned(),
+                &BuildFromContentOptions::default(),
+            ),
+            pack_path: PackPath::from_string("/file.txt".to_owned()),
+        }
+    }
+
+    #[test_case(ConflictPolicy::Error, None; "error")]
+    #[test_case(ConflictPolicy::FirstWins, Some(b"first".as_slice()); "first wins")]
+    #[test_case(ConflictPolicy::LastWins, Some(b"last".as_slice()); "last wins")]
+    fn file_pack_path_add_with_conflict_policy_returns_expected(
+        conflict_policy: ConflictPolicy,
+        expected: Option<&[u8]>,
+    ) {
+        let mut builder = Builder::new();
+        builder
+            .file_pack_path_add_with_conflict_policy(file_pack_path(b"first"), conflict_policy)
+            .unwrap();
+        let result = builder
+            .file_pack_path_add_with_conflict_policy(file_pack_path(b"last"), conflict_policy);
+
+        match expected {
+            Some(expected) => {
+                result.unwrap();
+                let pack = builder.finalize();
+                assert_eq!(&*pack.files_by_path["/file.txt"].content, expected);
+            }
+            None => {
+                result.unwrap_err();
+            }
+        }
+    }
+
+    #[test]
+    fn store_memory_load_memory_roundtrips() {
+        let mut builder = Builder::new();
+        builder
+            .file_pack_path_add(file_pack_path(b"content"))
+            .unwrap();
+        let pack = builder.finalize();
+
+        let serialized = store_memory(&pack, &StoreOptions::default()).unwrap();
+        let pack_loaded = load_memory(&serialized).unwrap();
+
+        let file = &pack.files_by_path["/file.txt"];
+        let file_loaded = &pack_loaded.files_by_path["/file.txt"];
+        assert_eq!(file_loaded.content, file.content);
+        assert_eq!(file_loaded.content_gzip, file.content_gzip);
+        assert_eq!(file_loaded.content_brotli, file.content_brotli);
+        assert_eq!(file_loaded.content_type, file.content_type);
+        assert_eq!(file_loaded.etag, file.etag);
+        assert_eq!(file_loaded.cache_control, file.cache_control);
+    }
+
+    #[test]
+    fn store_memory_signed_load_memory_signed_verifies() {
+        let signing_key = SigningKey::from_bytes(&[1; 32]);
+        let signing_key_other = SigningKey::from_bytes(&[2; 32]);
+
+        let mut builder = Builder::new();
+        builder
+            .file_pack_path_add(file_pack_path(b"content"))
+            .unwrap();
+        let pack = builder.finalize();
+
+        let serialized = store_memory(
+            &pack,
+            &StoreOptions {
+                signing_key: Some(signing_key.clone()),
+                ..StoreOptions::default()
+            },
+        )
+        .unwrap();
+
+        // signed pack can be loaded regularly
+        assert!(load_memory(&serialized).is_ok());
+        assert!(load_memory_signed(&serialized, &[signing_key.verifying_key()]).is_ok());
+        assert!(load_memory_signed(&serialized, &[signing_key_other.verifying_key()]).is_err());
+
+        // unsigned pack is rejected
+        let serialized_unsigned = store_memory(&pack, &StoreOptions::default()).unwrap();
+        assert!(load_memory_signed(&serialized_unsigned, &[signing_key.verifying_key()]).is_err());
+    }
+
+    #[test]
+    fn sign_memory_keeps_content_and_replaces_signature() {
+        let signing_key = SigningKey::from_bytes(&[1; 32]);
+        let signing_key_other = SigningKey::from_bytes(&[2; 32]);
+
+        let mut builder = Builder::new();
+        builder
+            .file_pack_path_add(file_pack_path(b"content"))
+            .unwrap();
+        let pack = builder.finalize();
+
+        // stored without checksum, sign must not add it
+        let serialized = store_memory(
+            &pack,
+            &StoreOptions {
+                checksum: false,
+                ..StoreOptions::default()
+            },
+        )
+        .unwrap();
+
+        let serialized_signed = sign_memory(&serialized, &signing_key).unwrap();
+        assert!(serialized_signed.starts_with(&serialized));
+        assert!(load_memory_signed(&serialized_signed, &[signing_key.verifying_key()]).is_ok());
+
+        // signing again replaces previous signature
+        let serialized_signed_other = sign_memory(&serialized_signed, &signing_key_other).unwrap();
+        assert_eq!(serialized_signed_other.len(), serialized_signed.len());
+        assert!(serialized_signed_other.starts_with(&serialized));
+        assert!(
+            load_memory_signed(
+                &serialized_signed_other,
+                &[signing_key_other.verifying_key()]
+            )
+            .is_ok()
+        );
+        assert!(
+            load_memory_signed(&serialized_signed_other, &[signing_key.verifying_key()]).is_err()
+        );
+
+        // store and sign produce the same signature
+        let serialized_stored_signed = store_memory(
+            &pack,
+            &StoreOptions {
+                checksum: false,
+                signing_key: Some(signing_key.clone()),
+                ..StoreOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(*serialized_stored_signed, *serialized_signed);
+    }
+
+    #[test]
+    fn store_file_replaces_existing() {
+        let directory_path = env::temp_dir().join(format!(
+            "web-static-pack-store-file-replaces-existing-{}",
+            process::id()
+        ));
+        fs::create_dir(&directory_path).unwrap();
+        let path = directory_path.join("test.pack");
+        fs::write(&path, b"previous").unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        }
+
+        let mut builder = Builder::new();
+        builder
+            .file_pack_path_add(file_pack_path(b"content"))
+            .unwrap();
+        let pack = builder.finalize();
+
+        store_file(&pack, &path, &StoreOptions::default()).unwrap();
+
+        let pack_loaded = load_file(&path).unwrap();
+        assert!(pack_loaded.files_by_path.contains_key("/file.txt"));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(
+                fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+                0o640
+            );
+        }
+
+        // no temporary files left
+        assert_eq!(fs::read_dir(&directory_path).unwrap().count(), 1);
+
+        fs::remove_dir_all(&directory_path).unwrap();
+    }
+
+    #[test]
+    fn store_file_concurrent_calls_succeed() {
+        let directory_path = env::temp_dir().join(format!(
+            "web-static-pack-store-file-concurrent-calls-succeed-{}",
+            process::id()
+        ));
+        fs::create_dir(&directory_path).unwrap();
+        let path = directory_path.join("test.pack");
+
+        let mut builder = Builder::new();
+        builder
+            .file_pack_path_add(file_pack_path(b"content"))
+            .unwrap();
+        let pack = builder.finalize();
+
+        thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for _ in 0..8 {
+                        store_file(&pack, &path, &StoreOptions::default()).unwrap();
+                    }
+                });
+            }
+        });
+
+        let pack_loaded = load_file(&path).unwrap();
+        assert!(pack_loaded.files_by_path.contains_key("/file.txt"));
+
+        // no temporary files left
+        assert_eq!(fs::read_dir(&directory_path).unwrap().count(), 1);
+
+        fs::remove_dir_all(&directory_path).unwrap();
+    }
+
+    #[test]
+    fn store_memory_is_deterministic() {
+        let pack_paths = ["/a.txt", "/b.txt", "/c/d.txt", "/e.txt"];
+
+        let serialized = |pack_paths: &mut dyn Iterator<Item = &&str>| {
+            let mut builder = Builder::new();
+            for pack_path in pack_paths {
+                builder
+                    .file_pack_path_add(FilePackPath {
+                        pack_path: PackPath::from_string((*pack_path).to_owned()),
+                        ..file_pack_path(b"content")
+                    })
+                    .unwrap();
+            }
+            let pack = builder.finalize();
+
+            store_memory(&pack, &StoreOptions::default()).unwrap()
+        };
+
+        assert!(*serialized(&mut pack_paths.iter()) == *serialized(&mut pack_paths.iter().rev()));
+    }
+}
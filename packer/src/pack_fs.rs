@@ -0,0 +1,503 @@
+//! Filesystem backed pack. Contains [PackFs], a [Pack] serving files directly
+//! from a directory, for development purposes.
+
+use crate::{
+    common::{cache_control::CacheControl, file::File, pack_path::PackPath},
+    file::{self, BuildFromPathOptions},
+    pack_path,
+};
+use elsa::sync::{FrozenMap, FrozenVec};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+use walkdir::WalkDir;
+use web_static_pack::pack::{Pack, PackEnumerate};
+
+/// Options for [PackFs].
+///
+/// If not sure what to set here, use [Default].
+#[derive(Debug)]
+pub struct PackFsOptions {
+    /// Build gzipped version of each file when it is read. Makes serving
+    /// slower, but lets `content-encoding` negotiation behave like with a
+    /// built `pack`.
+    pub use_gzip: bool,
+    /// Build brotli version of each file when it is read. Makes serving (much)
+    /// slower, but lets `content-encoding` negotiation behave like with a
+    /// built `pack`.
+    pub use_brotli: bool,
+
+    /// [CacheControl] set for all files.
+    pub cache_control: CacheControl,
+
+    /// Whether to follow links while looking up and enumerating files.
+    pub follow_links: bool,
+}
+impl Default for PackFsOptions {
+    fn default() -> Self {
+        Self {
+            use_gzip: false,
+            use_brotli: false,
+            // files may change at any time, browser should always revalidate
+            cache_control: CacheControl::NoCache,
+            follow_links: true,
+        }
+    }
+}
+
+/// File built from fs, with its pack path.
+#[derive(Debug)]
+struct Entry {
+    pack_path: String,
+    file: File,
+}
+
+/// State of the fs file [Entry] was built from.
+#[derive(PartialEq, Eq, Debug)]
+struct Stamp {
+    modified: Option<SystemTime>,
+    length: u64,
+}
+
+/// [Pack] reading files from directory on demand.
+///
+/// Meant to be used during development, eg. to serve frontend build output
+/// directory with the same [web_static_pack::responder::Responder] code path
+/// as used in production, without rebuilding a `pack` after each change.
+///
+/// Pack path is mapped to fs path relative to `root_path` with
+/// [pack_path::to_file_base_relative_path], so paths pointing outside of
+/// `root_path` are never resolved. `content-type` is guessed from path, as in
+/// [file::build_from_path].
+///
+/// Built files are cached and rebuilt when file modification time or size
+/// changes. Files are built without blocking other lookups, so slow builds (eg.
+/// with brotli enabled) of one file do not delay serving others.
+///
+/// As [Pack::get_file_by_path] returns references, each built version of a
+/// file is kept in memory until [PackFs] is dropped. Rebuilds not changing file
+/// content (eg. file saved again without changes) reuse previously built
+/// version, so memory grows only with actual content changes of served files.
+/// This is fine for development, but makes [PackFs] unsuitable for long running
+/// production servers.
+///
+/// Enumeration ([PackEnumerate]) walks the directory on each call. Only
+/// [PackEnumerate::files] and [PackEnumerate::files_by_prefix] build files,
+/// one by one as they are yielded, other methods only list directory content.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::path::PathBuf;
+/// # use web_static_pack::{pack::Pack, responder::Responder};
+/// # use web_static_pack_packer::pack_fs::{PackFs, PackFsOptions};
+/// #
+/// let pack = PackFs::new(PathBuf::from("frontend/dist"), PackFsOptions::default());
+/// assert!(pack.get_file_by_path("/index.html").is_some());
+///
+/// let responder = Responder::new(&pack);
+/// ```
+#[derive(Debug)]
+pub struct PackFs {
+    root_path: PathBuf,
+    options: PackFsOptions,
+
+    // every built version of every file, never removed, see type docs
+    entries: FrozenVec<Box<Entry>>,
+    entry_index_by_pack_path: Mutex<HashMap<String, (Stamp, usize)>>,
+
+    // pack paths seen during enumeration, so references can be returned
+    // without building files
+    pack_paths: FrozenMap<String, String>,
+}
+impl PackFs {
+    /// Creates new instance serving files from `root_path` directory.
+    pub fn new(
+        root_path: PathBuf,
+        options: PackFsOptions,
+    ) -> Self {
+        let entries = FrozenVec::new();
+        let entry_index_by_pack_path = Mutex::new(HashMap::new());
+        let pack_paths = FrozenMap::new();
+
+        Self {
+            root_path,
+            options,
+
+            entries,
+            entry_index_by_pack_path,
+
+            pack_paths,
+        }
+    }
+
+    /// Returns directory files are served from.
+    pub fn root_path(&self) -> &Path {
+        &self.root_path
+    }
+
+    /// Returns current [Entry] for `pack_path`, reading the file if it was
+    /// not read before or changed since.
+    fn entry_get(
+        &self,
+        pack_path: &str,
+    ) -> Option<&Entry> {
+        let path = self
+            .root_path
+            .join(pack_path::to_file_base_relative_path(pack_path).ok()?);
+
+        let stamp = self.stamp_get(&path)?;
+
+        // reuse cached entry if file did not change
+        if let Some(entry) = self.entry_cached_get(pack_path, &stamp) {
+            return Some(entry);
+        }
+
+        // building may take long (eg. with brotli), so lock is not held here
+        let file = file::build_from_path(
+            &path,
+            &BuildFromPathOptions {
+                use_gzip: self.options.use_gzip,
+                use_brotli: self.options.use_brotli,
+                content_type_override: None,
+                cache_control_override: Some(self.options.cache_control),
+            },
+        )
+        .ok()?;
+
+        // file changed while building, content may not match `stamp`
+        let stamp_changed = self.stamp_get(&path).as_ref() != Some(&stamp);
+
+        let mut entry_index_by_pack_path = self.entry_index_by_pack_path.lock().unwrap();
+
+        // file may have been built by other caller in the meantime
+        let entry_index_current =
+            entry_index_by_pack_path
+                .get(pack_path)
+                .and_then(|(stamp_cached, entry_index)| {
+                    if *stamp_cached == stamp {
+                        return Some(*entry_index);
+                    }
+
+                    // content did not change, eg. file was saved again
+                    let entry = self.entries.get(*entry_index)?;
+                    if !stamp_changed && entry.file.content == file.content {
+                        return Some(*entry_index);
+                    }
+
+                    None
+                });
+        let entry_index = match entry_index_current {
+            Some(entry_index) => entry_index,
+            None => self.entries.push_get_index(Box::new(Entry {
+                pack_path: pack_path.to_owned(),
+                file,
+            })),
+        };
+
+        // not cached if changed, so next call builds it again
+        if !stamp_changed {
+            entry_index_by_pack_path.insert(pack_path.to_owned(), (stamp, entry_index));
+        }
+
+        self.entries.get(entry_index)
+    }
+
+    /// Returns cached [Entry] for `pack_path` if it was built from file with
+    /// `stamp`.
+    fn entry_cached_get(
+        &self,
+        pack_path: &str,
+        stamp: &Stamp,
+    ) -> Option<&Entry> {
+        let entry_index_by_pack_path = self.entry_index_by_pack_path.lock().unwrap();
+
+        let (stamp_cached, entry_index) = entry_index_by_pack_path.get(pack_path)?;
+        if stamp_cached != stamp {
+            return None;
+        }
+
+        self.entries.get(*entry_index)
+    }
+
+    /// Returns [Stamp] of file at `path`, [None] if it's not a regular file.
+    ///
+    /// Links are resolved only if [PackFsOptions::follow_links] is set, so
+    /// files are found the same way as when enumerating.
+    fn stamp_get(
+        &self,
+        path: &Path,
+    ) -> Option<Stamp> {
+        let metadata = if self.options.follow_links {
+            fs::metadata(path).ok()?
+        } else {
+            // linked directories are not entered when enumerating either
+            let ancestor_linked = path
+                .ancestors()
+                .skip(1)
+                .take_while(|ancestor| *ancestor != self.root_path)
+                .any(|ancestor| {
+                    fs::symlink_metadata(ancestor)
+                        .map_or(true, |metadata| metadata.file_type().is_symlink())
+                });
+            if ancestor_linked {
+                return None;
+            }
+
+            fs::symlink_metadata(path).ok()?
+        };
+        if !metadata.is_file() {
+            return None;
+        }
+
+        let stamp = Stamp {
+            modified: metadata.modified().ok(),
+            length: metadata.len(),
+        };
+        Some(stamp)
+    }
+
+    /// Walks `root_path` and yields pack paths of all files found. Unreadable
+    /// entries are skipped.
+    fn pack_paths_walk(&self) -> impl Iterator<Item = PackPath> {
+        WalkDir::new(&self.root_path)
+            .follow_links(self.options.follow_links)
+            .into_iter()
+            .filter_map(|dir_entry| {
+                let dir_entry = dir_entry.ok()?;
+                if !dir_entry.file_type().is_file() {
+                    return None;
+                }
+
+                let pack_path = pack_path::from_file_base_relative_path(
+                    dir_entry.path().strip_prefix(&self.root_path).ok()?,
+                )
+                .ok()?;
+
+                Some(pack_path)
+            })
+    }
+}
+impl Pack for PackFs {
+    type File = File;
+
+    fn get_file_by_path(
+        &self,
+        path: &str,
+    ) -> Option<&Self::File> {
+        let entry = self.entry_get(path)?;
+        Some(&entry.file)
+    }
+}
+impl PackEnumerate for PackFs {
+    fn files(&self) -> impl Iterator<Item = (&str, &Self::File)> {
+        self.pack_paths_walk().filter_map(|pack_path| {
+            let entry = self.entry_get(&pack_path)?;
+            Some((entry.pack_path.as_str(), &entry.file))
+        })
+    }
+
+    fn paths(&self) -> impl Iterator<Item = &str> {
+        self.pack_paths_walk()
+            .map(|pack_path| match self.pack_paths.get(&*pack_path) {
+                Some(pack_path) => pack_path,
+                None => self
+                    .pack_paths
+                    .insert((*pack_path).to_owned(), (*pack_path).to_owned()),
+            })
+    }
+
+    // filtering by path first, so only matching files are built
+    fn files_by_prefix<'s>(
+        &'s self,
+        prefix: &str,
+    ) -> impl Iterator<Item = (&'s str, &'s Self::File)> {
+        self.pack_paths_walk()
+            .filter(move |pack_path| pack_path.starts_with(prefix))
+            .filter_map(|pack_path| {
+                let entry = self.entry_get(&pack_path)?;
+                Some((entry.pack_path.as_str(), &entry.file))
+            })
+    }
+
+    fn len(&self) -> usize {
+        self.pack_paths_walk().count()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pack_paths_walk().next().is_none()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PackFs, PackFsOptions};
+    use std::{
+        env, fs,
+        path::PathBuf,
+        process, thread,
+        time::{Duration, SystemTime},
+    };
+    use web_static_pack::pack::{Pack, PackEnumerate};
+
+    fn directory_path(name: &str) -> PathBuf {
+        let directory_path = env::temp_dir().join(format!(
+            "web-static-pack-pack-fs-{}-{}",
+            name,
+            process::id()
+        ));
+        let _ = fs::remove_dir_all(&directory_path);
+        fs::create_dir_all(directory_path.join("assets")).unwrap();
+        directory_path
+    }
+
+    #[test]
+    fn get_file_by_path_reads_and_reloads() {
+        let directory_path = directory_path("reload");
+        let file_path = directory_path.join("assets").join("style.css");
+        fs::write(&file_path, b"body {}").unwrap();
+
+        let pack = PackFs::new(directory_path.clone(), PackFsOptions::default());
+
+        let file = pack.get_file_by_path("/assets/style.css").unwrap();
+        assert_eq!(&*file.content, b"body {}");
+        assert_eq!(file.content_type, "text/css; charset=utf-8");
+
+        // unchanged file is served from cache
+        assert!(std::ptr::eq(
+            file,
+            pack.get_file_by_path("/assets/style.css").unwrap()
+        ));
+
+        // modified file is read again, previous reference stays valid
+        fs::write(&file_path, b"body { color: red; }").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&file_path)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(10))
+            .unwrap();
+        let file_modified = pack.get_file_by_path("/assets/style.css").unwrap();
+        assert_eq!(&*file_modified.content, b"body { color: red; }");
+        assert_eq!(&*file.content, b"body {}");
+
+        // file saved again without changes reuses already built version
+        fs::File::options()
+            .write(true)
+            .open(&file_path)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(20))
+            .unwrap();
+        assert!(std::ptr::eq(
+            file_modified,
+            pack.get_file_by_path("/assets/style.css").unwrap()
+        ));
+        assert_eq!(pack.entries.len(), 2);
+
+        assert!(pack.get_file_by_path("/assets").is_none());
+        assert!(pack.get_file_by_path("/missing.css").is_none());
+        assert!(pack.get_file_by_path("/../style.css").is_none());
+
+        fs::remove_dir_all(&directory_path).unwrap();
+    }
+
+    #[test]
+    fn files_lists_directory() {
+        let directory_path = directory_path("files");
+        fs::write(directory_path.join("index.html"), b"<html></html>").unwrap();
+        fs::write(directory_path.join("assets").join("app.js"), b"").unwrap();
+
+        let pack = PackFs::new(directory_path.clone(), PackFsOptions::default());
+
+        let mut paths = pack.paths().collect::<Vec<_>>();
+        paths.sort_unstable();
+        assert_eq!(paths, ["/assets/app.js", "/index.html"]);
+        assert_eq!(pack.len(), 2);
+        assert!(!pack.is_empty());
+
+        // listing paths does not build files
+        assert_eq!(pack.entries.len(), 0);
+
+        let paths = pack
+            .files_by_prefix("/assets/")
+            .map(|(path, _file)| path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["/assets/app.js"]);
+        assert_eq!(pack.entries.len(), 1);
+
+        fs::remove_dir_all(&directory_path).unwrap();
+    }
+
+    #[test]
+    fn get_file_by_path_builds_once_when_concurrent() {
+        let directory_path = directory_path("concurrent");
+        fs::write(directory_path.join("index.html"), b"<html></html>").unwrap();
+
+        let pack = PackFs::new(
+            directory_path.clone(),
+            PackFsOptions {
+                use_brotli: true,
+                ..PackFsOptions::default()
+            },
+        );
+
+        thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| pack.get_file_by_path("/index.html").unwrap());
+            }
+        });
+        assert_eq!(pack.entries.len(), 1);
+
+        fs::remove_dir_all(&directory_path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn follow_links_applies_to_lookup_and_enumeration() {
+        use std::os::unix::fs::symlink;
+
+        let directory_path = directory_path("links");
+        let target_path = directory_path.join("target");
+        fs::create_dir_all(&target_path).unwrap();
+        fs::write(target_path.join("file.txt"), b"content").unwrap();
+        symlink(
+            target_path.join("file.txt"),
+            directory_path.join("file.txt"),
+        )
+        .unwrap();
+        symlink(&target_path, directory_path.join("assets").join("linked")).unwrap();
+
+        for (follow_links, expected) in [
+            (
+                true,
+                &["/assets/linked/file.txt", "/file.txt", "/target/file.txt"][..],
+            ),
+            (false, &["/target/file.txt"][..]),
+        ] {
+            let pack = PackFs::new(
+                directory_path.clone(),
+                PackFsOptions {
+                    follow_links,
+                    ..PackFsOptions::default()
+                },
+            );
+
+            let mut paths = pack.paths().collect::<Vec<_>>();
+            paths.sort_unstable();
+            assert_eq!(paths, expected);
+
+            for path in ["/assets/linked/file.txt", "/file.txt", "/target/file.txt"] {
+                assert_eq!(
+                    pack.get_file_by_path(path).is_some(),
+                    expected.contains(&path)
+                );
+            }
+        }
+
+        fs::remove_dir_all(&directory_path).unwrap();
+    }
+}
@@ -0,0 +1,191 @@
+//! Pack wrapper for serving web applications. Contains [PackIndexFallback],
+//! a [Pack] resolving directory index files and fallback file.
+
+use web_static_pack::pack::{Pack, PackEnumerate};
+
+/// Options for [PackIndexFallback].
+///
+/// If not sure what to set here, use [Default].
+#[derive(Debug)]
+pub struct PackIndexFallbackOptions {
+    /// File name looked up for directory paths (ending with `/`), eg. `/` is
+    /// resolved to `/index.html`. [None] disables index resolving.
+    pub index_file_name: Option<String>,
+
+    /// Path of file returned for paths not found in `pack`, eg. `/index.html`
+    /// for single page applications using client side routing. [None]
+    /// disables fallback.
+    pub fallback_path: Option<String>,
+}
+impl Default for PackIndexFallbackOptions {
+    fn default() -> Self {
+        Self {
+            index_file_name: Some("index.html".to_owned()),
+            fallback_path: None,
+        }
+    }
+}
+
+/// [Pack] wrapping another `pack`, additionally resolving directory index
+/// files and fallback file for paths not found.
+///
+/// Lookup order for a path is:
+/// - file with exactly matching path,
+/// - if path ends with `/`, index file inside this directory (see
+///   [PackIndexFallbackOptions::index_file_name]),
+/// - fallback file (see [PackIndexFallbackOptions::fallback_path]).
+///
+/// Please note that with fallback enabled, every path is found, including
+/// missing assets, which will get fallback file content instead of `404`.
+///
+/// Enumeration ([PackEnumerate::files]) yields only files of the wrapped
+/// `pack`.
+///
+/// # Examples
+///
+/// ```
+/// # use web_static_pack::pack::Pack;
+/// # use web_static_pack_packer::pack_index_fallback::{
+/// #     PackIndexFallback, PackIndexFallbackOptions,
+/// # };
+/// # let pack = web_static_pack_packer::common::pack::Pack {
+/// #     files_by_path: Default::default(),
+/// # };
+/// #
+/// let pack = PackIndexFallback::new(
+///     pack,
+///     PackIndexFallbackOptions {
+///         fallback_path: Some("/index.html".to_owned()),
+///         ..PackIndexFallbackOptions::default()
+///     },
+/// );
+/// ```
+#[derive(Debug)]
+pub struct PackIndexFallback<P>
+where
+    P: Pack,
+{
+    pack: P,
+    options: PackIndexFallbackOptions,
+}
+impl<P> PackIndexFallback<P>
+where
+    P: Pack,
+{
+    /// Creates new instance wrapping `pack`.
+    pub fn new(
+        pack: P,
+        options: PackIndexFallbackOptions,
+    ) -> Self {
+        Self { pack, options }
+    }
+
+    /// Returns wrapped `pack`.
+    pub fn pack(&self) -> &P {
+        &self.pack
+    }
+}
+impl<P> Pack for PackIndexFallback<P>
+where
+    P: Pack,
+{
+    type File = P::File;
+
+    fn get_file_by_path(
+        &self,
+        path: &str,
+    ) -> Option<&Self::File> {
+        if let Some(file) = self.pack.get_file_by_path(path) {
+            return Some(file);
+        }
+
+        if let Some(index_file_name) = &self.options.index_file_name
+            && path.ends_with('/')
+            && let Some(file) = self
+                .pack
+                .get_file_by_path(&format!("{path}{index_file_name}"))
+        {
+            return Some(file);
+        }
+
+        if let Some(fallback_path) = &self.options.fallback_path {
+            return self.pack.get_file_by_path(fallback_path);
+        }
+
+        None
+    }
+}
+impl<P> PackEnumerate for PackIndexFallback<P>
+where
+    P: PackEnumerate,
+{
+    fn files(&self) -> impl Iterator<Item = (&str, &Self::File)> {
+        self.pack.files()
+    }
+
+    fn len(&self) -> usize {
+        self.pack.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pack.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PackIndexFallback, PackIndexFallbackOptions};
+    use crate::{
+        common::{pack::Pack as Pack_, pack_path::PackPath},
+        file::{BuildFromContentOptions, build_from_content},
+    };
+    use test_case::test_case;
+    use web_static_pack::pack::Pack;
+
+    fn pack() -> Pack_ {
+        let files_by_path = ["/index.html", "/docs/index.html", "/app.js"]
+            .into_iter()
+            .map(|path| {
+                (
+                    PackPath::from_string(path.to_owned()),
+                    build_from_content(
+                        path.as_bytes().into(),
+                        "text/plain; charset=utf-8".to_owned(),
+                        &BuildFromContentOptions::default(),
+                    ),
+                )
+            })
+            .collect();
+
+        Pack_ { files_by_path }
+    }
+
+    #[test_case(None, None, "/app.js", Some("/app.js"); "exact match")]
+    #[test_case(None, None, "/", None; "index disabled")]
+    #[test_case(Some("index.html"), None, "/", Some("/index.html"); "root index")]
+    #[test_case(Some("index.html"), None, "/docs/", Some("/docs/index.html"); "directory index")]
+    #[test_case(Some("index.html"), None, "/docs", None; "directory without slash")]
+    #[test_case(Some("index.html"), None, "/missing/", None; "missing directory")]
+    #[test_case(None, Some("/index.html"), "/route/in/app", Some("/index.html"); "fallback")]
+    #[test_case(Some("index.html"), Some("/index.html"), "/docs/", Some("/docs/index.html"); "index before fallback")]
+    #[test_case(None, Some("/missing.html"), "/route", None; "missing fallback")]
+    fn get_file_by_path_returns_expected(
+        index_file_name: Option<&str>,
+        fallback_path: Option<&str>,
+        path: &str,
+        expected: Option<&str>,
+    ) {
+        let pack = PackIndexFallback::new(
+            pack(),
+            PackIndexFallbackOptions {
+                index_file_name: index_file_name.map(str::to_owned),
+                fallback_path: fallback_path.map(str::to_owned),
+            },
+        );
+
+        let content = pack
+            .get_file_by_path(path)
+            .map(|file| str::from_utf8(&file.content).unwrap());
+        assert_eq!(content, expected);
+    }
+}
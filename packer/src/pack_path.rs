@@ -1,11 +1,13 @@
 //! Pack path helpers. Contains [from_file_base_relative_path] that creates pack
-//! paths from fs paths.
+//! paths from fs paths, [to_file_base_relative_path] doing the opposite and
+//! [glob_set_build] for matching pack paths.
 
 use crate::common::pack_path::PackPath;
-use anyhow::{Error, anyhow, ensure};
+use anyhow::{Context, Error, anyhow, ensure};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
 use std::{
     iter,
-    path::{Component, Path},
+    path::{Component, Path, PathBuf},
 };
 
 /// Creates pack path (eg. "/dir1/dir2/file.html") from relative fs path (eg.
@@ -63,9 +65,99 @@ pub fn from_file_base_relative_path(file_base_relative_path: &Path) -> Result<Pa
     Ok(pack_path)
 }
 
+/// Creates relative fs path (eg. "dir1/dir2/file.html") from pack path (eg.
+/// "/dir1/dir2/file.html"). This is the opposite of
+/// [from_file_base_relative_path].
+///
+/// Fails for paths that are not safe to be joined with base directory, eg.
+/// containing `..` or empty components, so the result never points outside of
+/// it.
+///
+/// # Examples
+///
+/// ```
+/// # use anyhow::Error;
+/// # use std::path::PathBuf;
+/// # use web_static_pack_packer::pack_path::to_file_base_relative_path;
+/// #
+/// # fn main() -> Result<(), Error> {
+/// #
+/// assert_eq!(
+///     to_file_base_relative_path("/path/to/file.txt")?,
+///     PathBuf::from("path").join("to").join("file.txt"),
+/// );
+/// assert!(to_file_base_relative_path("/path/../../file.txt").is_err());
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn to_file_base_relative_path(pack_path: &str) -> Result<PathBuf, Error> {
+    let pack_path_components = pack_path
+        .strip_prefix('/')
+        .ok_or_else(|| anyhow!("pack path must start with /"))?
+        .split('/');
+
+    let file_base_relative_path = pack_path_components
+        .map(|pack_path_component| {
+            // component must be exactly one normal fs path component, this
+            // rejects things like '', '.', '..', 'C:' or 'a\b' on windows
+            let mut components = Path::new(pack_path_component).components();
+            ensure!(
+                matches!(
+                    (components.next(), components.next()),
+                    (Some(Component::Normal(component)), None) if component == pack_path_component
+                ),
+                "pack path must contain only standard path items, got {:?}",
+                pack_path_component
+            );
+
+            Ok(pack_path_component)
+        })
+        .collect::<Result<PathBuf, Error>>()?;
+
+    Ok(file_base_relative_path)
+}
+
+/// Builds [GlobSet] matching pack paths from list of glob patterns.
+///
+/// Patterns are matched against whole pack path (including leading `/`). `*`
+/// does not match path separator, use `**` to match across directories, eg.
+/// `/assets/**/*.css`.
+///
+/// # Examples
+///
+/// ```
+/// # use anyhow::Error;
+/// # use web_static_pack_packer::pack_path::glob_set_build;
+/// #
+/// # fn main() -> Result<(), Error> {
+/// #
+/// let glob_set = glob_set_build(["/assets/**", "/*.html"])?;
+///
+/// assert!(glob_set.is_match("/assets/css/style.css"));
+/// assert!(glob_set.is_match("/index.html"));
+/// assert!(!glob_set.is_match("/blog/index.html"));
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn glob_set_build<'a>(patterns: impl IntoIterator<Item = &'a str>) -> Result<GlobSet, Error> {
+    let mut glob_set = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .with_context(|| pattern.to_owned())?;
+        glob_set.add(glob);
+    }
+    let glob_set = glob_set.build()?;
+
+    Ok(glob_set)
+}
+
 #[cfg(test)]
 mod test {
-    use super::from_file_base_relative_path;
+    use super::{from_file_base_relative_path, glob_set_build, to_file_base_relative_path};
     use crate::common::pack_path::PackPath;
     use std::path::{Path, PathBuf};
     use test_case::test_case;
@@ -91,4 +183,36 @@ mod test {
     ) {
         assert_eq!(&from_file_base_relative_path(path).unwrap(), expected);
     }
+
+    #[test_case("/somefile", Some(PathBuf::from("somefile")); "base file")]
+    #[test_case("/dir/file.html", Some(PathBuf::from("dir").join("file.html")); "nested file")]
+    #[test_case("somefile", None; "missing leading slash")]
+    #[test_case("/", None; "empty")]
+    #[test_case("/dir//file.html", None; "empty component")]
+    #[test_case("/dir/./file.html", None; "current directory")]
+    #[test_case("/../file.html", None; "parent directory")]
+    fn to_file_base_relative_path_returns_expected(
+        pack_path: &str,
+        expected: Option<PathBuf>,
+    ) {
+        assert_eq!(to_file_base_relative_path(pack_path).ok(), expected);
+    }
+
+    #[test_case(&["/assets/**"], "/assets/css/style.css", true; "double star matches nested")]
+    #[test_case(&["/assets/*"], "/assets/css/style.css", false; "single star does not match nested")]
+    #[test_case(&["/*.html"], "/index.html", true; "extension match")]
+    #[test_case(&["/*.html", "/*.css"], "/style.css", true; "any of patterns")]
+    #[test_case(&[], "/index.html", false; "no patterns")]
+    fn glob_set_build_matches_expected(
+        patterns: &[&str],
+        pack_path: &str,
+        expected: bool,
+    ) {
+        assert_eq!(
+            glob_set_build(patterns.iter().copied())
+                .unwrap()
+                .is_match(pack_path),
+            expected
+        );
+    }
 }
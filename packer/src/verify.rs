@@ -0,0 +1,219 @@
+//! Verification helpers. Contains [verify] function, checking consistency of
+//! files inside existing [Pack].
+
+use crate::common::{
+    file::{File, etag_from_content},
+    pack::Pack,
+};
+use itertools::Itertools;
+use serde::Serialize;
+use std::fmt;
+use web_static_pack::loader::{ContentCompressedError, content_brotli_check, content_gzip_check};
+
+/// Single problem found in a file by [verify].
+#[derive(Serialize, PartialEq, Eq, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Problem {
+    /// `gzip` content cannot be decompressed.
+    ContentGzipInvalid,
+    /// `gzip` content decompresses to something different than `identity`
+    /// content.
+    ContentGzipMismatch,
+    /// `brotli` content cannot be decompressed.
+    ContentBrotliInvalid,
+    /// `brotli` content decompresses to something different than `identity`
+    /// content.
+    ContentBrotliMismatch,
+    /// Stored `ETag` does not match the one calculated from content.
+    EtagMismatch {
+        /// `ETag` stored in `pack`.
+        stored: String,
+        /// `ETag` calculated from content.
+        calculated: String,
+    },
+}
+impl fmt::Display for Problem {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            Problem::ContentGzipInvalid => write!(f, "gzip content cannot be decompressed"),
+            Problem::ContentGzipMismatch => {
+                write!(f, "gzip content does not match identity content")
+            }
+            Problem::ContentBrotliInvalid => write!(f, "br content cannot be decompressed"),
+            Problem::ContentBrotliMismatch => {
+                write!(f, "br content does not match identity content")
+            }
+            Problem::EtagMismatch { stored, calculated } => write!(
+                f,
+                "etag mismatch (stored: {stored}, calculated: {calculated})"
+            ),
+        }
+    }
+}
+
+/// [Problem] with path of the file it was found in.
+#[derive(Serialize, Debug)]
+pub struct FileProblem {
+    /// Path inside the `pack`.
+    pub path: String,
+    /// The problem.
+    pub problem: Problem,
+}
+impl fmt::Display for FileProblem {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.problem)
+    }
+}
+
+/// Result of [verify].
+#[derive(Serialize, Debug)]
+pub struct Verify {
+    /// Number of verified files.
+    pub files_count: usize,
+    /// Problems found, sorted by path.
+    pub problems: Box<[FileProblem]>,
+}
+impl Verify {
+    /// Returns `true` if no problems were found.
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+impl fmt::Display for Verify {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        for problem in &self.problems {
+            writeln!(f, "{problem}")?;
+        }
+        writeln!(
+            f,
+            "{} files verified, {} problems found",
+            self.files_count,
+            self.problems.len()
+        )?;
+        Ok(())
+    }
+}
+
+/// Verifies consistency of all files in `pack`.
+///
+/// For each file checks if compressed versions (`gzip`, `brotli`) decompress to
+/// the `identity` content and if stored `ETag` matches the one calculated from
+/// content. These are the same checks [web_static_pack::loader::load_checked]
+/// makes when requested in its options, but all problems are collected instead
+/// of failing on the first one.
+///
+/// Archive structure and header values are validated earlier, when loading
+/// `pack` with [crate::pack::load_file] or [crate::pack::load_memory].
+pub fn verify(pack: &Pack) -> Verify {
+    let problems = pack
+        .files_by_path
+        .iter()
+        .sorted_unstable_by(|(pack_path_a, _), (pack_path_b, _)| {
+            (***pack_path_a).cmp(&***pack_path_b)
+        })
+        .flat_map(|(pack_path, file)| {
+            file_problems(file).map(|problem| FileProblem {
+                path: pack_path.to_string(),
+                problem,
+            })
+        })
+        .collect::<Box<[_]>>();
+
+    Verify {
+        files_count: pack.files_by_path.len(),
+        problems,
+    }
+}
+
+/// Returns all problems found in `file`.
+fn file_problems(file: &File) -> impl Iterator<Item = Problem> {
+    let content_gzip_problem = file.content_gzip.as_ref().and_then(|content_gzip| {
+        match content_gzip_check(&file.content, content_gzip) {
+            Ok(()) => None,
+            Err(ContentCompressedError::Invalid) => Some(Problem::ContentGzipInvalid),
+            Err(ContentCompressedError::Mismatch) => Some(Problem::ContentGzipMismatch),
+        }
+    });
+
+    let content_brotli_problem = file.content_brotli.as_ref().and_then(|content_brotli| {
+        match content_brotli_check(&file.content, content_brotli) {
+            Ok(()) => None,
+            Err(ContentCompressedError::Invalid) => Some(Problem::ContentBrotliInvalid),
+            Err(ContentCompressedError::Mismatch) => Some(Problem::ContentBrotliMismatch),
+        }
+    });
+
+    let etag = etag_from_content(&file.content);
+    let etag_problem = (etag != file.etag).then(|| Problem::EtagMismatch {
+        stored: file.etag.clone(),
+        calculated: etag,
+    });
+
+    [content_gzip_problem, content_brotli_problem, etag_problem]
+        .into_iter()
+        .flatten()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Problem, verify};
+    use crate::{
+        common::{file::File, pack::Pack, pack_path::PackPath},
+        file::{BuildFromContentOptions, build_from_content},
+        file_pack_path::FilePackPath,
+        pack::Builder,
+    };
+
+    fn pack(modify: impl FnOnce(&mut File)) -> Pack {
+        let mut file = build_from_content(
+            Box::from(b"lorem ipsum dolor sit amet ".repeat(16)),
+            "text/plain; charset=utf-8".to_owned(),
+            &BuildFromContentOptions::default(),
+        );
+        modify(&mut file);
+
+        let mut builder = Builder::new();
+        builder
+            .file_pack_path_add(FilePackPath {
+                file,
+                pack_path: PackPath::from_string("/file.txt".to_owned()),
+            })
+            .unwrap();
+        builder.finalize()
+    }
+
+    #[test]
+    fn verify_accepts_valid() {
+        let verify = verify(&pack(|_file| {}));
+
+        assert_eq!(verify.files_count, 1);
+        assert!(verify.is_valid());
+    }
+
+    #[test]
+    fn verify_detects_problems() {
+        let verify = verify(&pack(|file| {
+            file.content_gzip = Some(Box::new(*b"not a gzip"));
+            file.content = Box::new(*b"changed");
+        }));
+
+        let problems = verify
+            .problems
+            .iter()
+            .map(|file_problem| &file_problem.problem)
+            .collect::<Vec<_>>();
+        assert_eq!(problems.len(), 3);
+        assert_eq!(*problems[0], Problem::ContentGzipInvalid);
+        assert_eq!(*problems[1], Problem::ContentBrotliMismatch);
+        assert!(matches!(problems[2], Problem::EtagMismatch { .. }));
+    }
+}
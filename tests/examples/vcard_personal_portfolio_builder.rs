@@ -18,6 +18,7 @@ fn main() -> Result<(), Error> {
     web_static_pack_packer::pack::store_file(
         pack,
         &directory.join("vcard-personal-portfolio.pack"),
+        &web_static_pack_packer::pack::StoreOptions::default(),
     )?;
 
     Ok(())
@@ -1,30 +1,19 @@
 #![doc(hidden)]
 
 use anyhow::{Context, Error, anyhow, ensure};
-use futures::{
-    channel::oneshot,
-    future::{Either, select},
-    pin_mut,
-};
+use futures::channel::oneshot;
 use http::header;
-use hyper::{server::conn::http1, service::service_fn};
-use hyper_util::{rt::TokioIo, server::graceful::GracefulShutdown};
-use memmap2::Mmap;
-use ouroboros::self_referencing;
 use reqwest::Client;
 use std::{
-    convert::Infallible,
-    fs::File,
-    mem::transmute,
     net::{Ipv4Addr, SocketAddr, SocketAddrV4},
     path::PathBuf,
-    sync::LazyLock,
+    sync::{Arc, LazyLock},
 };
 use tokio::net::TcpListener;
 
 // builds [web_static_pack_common::pack::Pack] from
 // data/vcard-personal-portfolio
-fn build_vcard_personal_portfolio() -> Result<web_static_pack_common::pack::Pack, Error> {
+pub fn build_vcard_personal_portfolio() -> Result<web_static_pack_common::pack::Pack, Error> {
     let mut pack = web_static_pack_packer::pack::Builder::new();
     pack.file_pack_paths_add(web_static_pack_packer::directory::search(
         &PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -45,41 +34,36 @@ pub fn build_vcard_personal_portfolio_cached() -> &'static web_static_pack_commo
 }
 
 // loads (mmaps) data/vcard-personal-portfolio.pack
-#[self_referencing]
-struct VCardPersonalPortfolioMMap {
-    mmap: Mmap,
-
-    #[borrows(mmap)]
-    pack_archived: &'this web_static_pack_common::pack::PackArchived,
-}
-fn load_vcard_personal_portfolio() -> Result<VCardPersonalPortfolioMMap, Error> {
+pub fn load_vcard_personal_portfolio()
+-> Result<web_static_pack::pack_owned::PackOwned<web_static_pack::pack_owned::Mmap>, Error> {
     let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
         .join("data")
         .join("vcard-personal-portfolio.pack");
 
-    let file =
-        File::open(path).context("you probably need to run builder example from this crate")?;
-    let mmap = unsafe { Mmap::map(&file)? };
-    // let pack_archived = unsafe { web_static_pack::loader::load(&*mmap) }?;
-
-    let inner = VCardPersonalPortfolioMMap::try_new(mmap, |mmap| unsafe {
-        web_static_pack::loader::load(mmap)
-    })?;
+    // SAFETY: test data is only replaced by builder example, atomically
+    let pack = unsafe {
+        web_static_pack::pack_owned::PackOwned::map_file(
+            &path,
+            &web_static_pack::loader::LoadCheckedOptions::default(),
+        )
+    }
+    .context("you probably need to run builder example from this crate")?;
 
-    Ok(inner)
+    Ok(pack)
 }
 pub fn load_vcard_personal_portfolio_cached() -> &'static web_static_pack_common::pack::PackArchived
 {
-    static CACHE: LazyLock<VCardPersonalPortfolioMMap> =
-        LazyLock::new(|| load_vcard_personal_portfolio().unwrap());
-    CACHE.borrow_pack_archived()
+    static CACHE: LazyLock<
+        web_static_pack::pack_owned::PackOwned<web_static_pack::pack_owned::Mmap>,
+    > = LazyLock::new(|| load_vcard_personal_portfolio().unwrap());
+    CACHE.pack()
 }
 
 // runs a http server serving pack, listening on bind or local emphemeric port
 // if not set, notifying bind_ready_sender when server is ready and where is
 // listening and shuts down when shutdown_receiver yields
 pub async fn serve_pack<P>(
-    pack: &P,
+    pack: &'static P,
     bind: Option<SocketAddr>,
     bind_ready_sender: Option<oneshot::Sender<SocketAddr>>,
     shutdown_receiver: oneshot::Receiver<()>,
@@ -89,33 +73,57 @@ where
 {
     log::trace!("staring server");
 
-    // pin shutdown future
-    pin_mut!(shutdown_receiver);
-
     // make responder from `pack`
-    let responder = web_static_pack::responder::Responder::new(pack);
+    let responder = web_static_pack::responder::ResponderOwned::new(Arc::new(pack));
 
-    // hyper requires service to be static
-    // we use graceful, no connections will outlive server function
-    let responder = unsafe {
-        transmute::<
-            &web_static_pack::responder::Responder<'_, P>,
-            &web_static_pack::responder::Responder<'static, P>,
-        >(&responder)
-    };
+    // use ephemeric port
+    let bind = bind.unwrap_or(SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0)));
 
-    // make hyper service
-    let service_fn = service_fn(|request| async {
-        let (parts, _body) = request.into_parts();
+    // server listener
+    let listener = TcpListener::bind(bind).await?;
 
-        log::info!("serving {}", parts.uri);
-        let response = responder.respond_flatten(&parts.method, parts.uri.path(), &parts.headers);
+    // get final listening port
+    let bind = listener.local_addr()?;
+    log::trace!("listening on {bind}");
 
-        Ok::<_, Infallible>(response)
-    });
+    // notify that server is ready
+    if let Some(bind_ready_sender) = bind_ready_sender {
+        bind_ready_sender.send(bind).unwrap();
+    }
 
-    // graceful shutdown watcher
-    let graceful = GracefulShutdown::new();
+    // main loop, with graceful shutdown
+    log::trace!("entering main server loop");
+    web_static_pack::server::serve_listener(
+        listener,
+        responder,
+        async move {
+            shutdown_receiver.await.unwrap();
+            log::trace!("got exit signal");
+        },
+        &web_static_pack::server::ServeOptions::default(),
+    )
+    .await;
+
+    log::trace!("exiting server");
+    Ok(())
+}
+
+// like [serve_pack], but serves with axum, using
+// [web_static_pack::axum::router]
+pub async fn serve_pack_axum<P>(
+    pack: Arc<P>,
+    bind: Option<SocketAddr>,
+    bind_ready_sender: Option<oneshot::Sender<SocketAddr>>,
+    shutdown_receiver: oneshot::Receiver<()>,
+) -> Result<(), Error>
+where
+    P: web_static_pack::pack::Pack + Send + Sync + 'static,
+{
+    log::trace!("staring server");
+
+    // make router from `pack`
+    let router: axum::Router =
+        web_static_pack::axum::router(web_static_pack::responder::ResponderOwned::new(pack));
 
     // use ephemeric port
     let bind = bind.unwrap_or(SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0)));
@@ -132,36 +140,14 @@ where
         bind_ready_sender.send(bind).unwrap();
     }
 
-    // main loop
+    // main loop, with graceful shutdown
     log::trace!("entering main server loop");
-    loop {
-        let listener_accept = listener.accept();
-        pin_mut!(listener_accept);
-
-        match select(listener_accept, &mut shutdown_receiver).await {
-            Either::Left((result, _)) => {
-                let (stream, _remote_address) = result?;
-                log::trace!("new connection");
-
-                let io = TokioIo::new(stream);
-
-                let connection = http1::Builder::new().serve_connection(io, service_fn);
-                let graceful_connection = graceful.watch(connection);
-
-                tokio::spawn(async move {
-                    graceful_connection.await.unwrap();
-                });
-            }
-            Either::Right((result, _)) => {
-                result.unwrap();
-                log::trace!("got exit signal");
-                break;
-            }
-        }
-    }
-
-    log::trace!("waiting for active connections to shutdown");
-    graceful.shutdown().await;
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async move {
+            shutdown_receiver.await.unwrap();
+            log::trace!("got exit signal");
+        })
+        .await?;
 
     log::trace!("exiting server");
     Ok(())
@@ -1,6 +1,8 @@
 use std::collections::HashSet;
+use web_static_pack_packer::pack::{StoreOptions, store_memory};
 use web_static_pack_tests::{
-    build_vcard_personal_portfolio_cached, load_vcard_personal_portfolio_cached,
+    build_vcard_personal_portfolio, build_vcard_personal_portfolio_cached,
+    load_vcard_personal_portfolio_cached,
 };
 
 #[test]
@@ -52,6 +54,18 @@ fn builder_builds_pack_with_same_contents() {
         });
 }
 
+#[test]
+fn builder_builds_byte_identical_packs() {
+    // two independent builds from the same source files
+    let pack_1 = build_vcard_personal_portfolio().unwrap();
+    let pack_2 = build_vcard_personal_portfolio().unwrap();
+
+    let serialized_1 = store_memory(&pack_1, &StoreOptions::default()).unwrap();
+    let serialized_2 = store_memory(&pack_2, &StoreOptions::default()).unwrap();
+
+    assert!(*serialized_1 == *serialized_2);
+}
+
 #[test]
 fn loader_loads_correctly_prebuilt_pack() {
     let pack_archived = load_vcard_personal_portfolio_cached();
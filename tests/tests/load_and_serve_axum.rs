@@ -0,0 +1,38 @@
+use futures::{channel::oneshot, try_join};
+use std::{net::SocketAddr, sync::Arc};
+use web_static_pack::pack::PackEnumerate;
+use web_static_pack_tests::{download_verify_pack, load_vcard_personal_portfolio, serve_pack_axum};
+
+#[tokio::test(flavor = "current_thread")]
+async fn client_downloads_verifies_served_by_axum_server() {
+    // `pack` to run both server and client on
+    let pack = Arc::new(load_vcard_personal_portfolio().unwrap());
+
+    // all paths from `pack` used for test
+    let pack_paths = pack.paths().collect::<Vec<_>>();
+
+    // makes client wait for server to become ready and turns it off when completed
+    let (bind_ready_sender, bind_ready_receiver) = oneshot::channel::<SocketAddr>();
+    let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
+
+    // server
+    let server = serve_pack_axum(
+        pack.clone(),
+        None,
+        Some(bind_ready_sender),
+        shutdown_receiver,
+    );
+
+    // client
+    let download_verifier = async {
+        let bind = bind_ready_receiver.await?;
+
+        download_verify_pack(&*pack, &pack_paths, bind).await?;
+
+        shutdown_sender.send(()).unwrap();
+        Ok(())
+    };
+
+    // wait for all
+    try_join!(server, download_verifier).unwrap();
+}
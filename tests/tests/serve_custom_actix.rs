@@ -0,0 +1,246 @@
+use actix_web::{
+    App,
+    body::MessageBody,
+    dev::ServiceResponse,
+    http::{Method, StatusCode, header},
+    test::{TestRequest, call_service, init_service},
+};
+use std::sync::Arc;
+use test_case::test_case;
+use web_static_pack::{
+    cache_control::CacheControl,
+    responder::{QueryPolicy, ResponderOptions, ResponderOwned},
+};
+
+struct FileMock {
+    cache_control_max: bool,
+}
+impl web_static_pack::file::File for FileMock {
+    fn content(&self) -> &[u8] {
+        b"content-identity-is-the-longest-and-least-preferred-option"
+    }
+    fn content_gzip(&self) -> Option<&[u8]> {
+        Some(b"content-gzip-longer-than-brotli")
+    }
+    fn content_brotli(&self) -> Option<&[u8]> {
+        Some(b"content-brotli")
+    }
+
+    fn content_type(&self) -> http::HeaderValue {
+        http::HeaderValue::from_static("text/plain; charset=utf-8")
+    }
+    fn etag(&self) -> http::HeaderValue {
+        http::HeaderValue::from_static("\"etagvalue\"")
+    }
+    fn cache_control(&self) -> CacheControl {
+        if self.cache_control_max {
+            CacheControl::MaxCache
+        } else {
+            CacheControl::NoCache
+        }
+    }
+}
+static FILE_MAX_CACHE: FileMock = FileMock {
+    cache_control_max: true,
+};
+static FILE_NO_CACHE: FileMock = FileMock {
+    cache_control_max: false,
+};
+struct PackMock;
+impl web_static_pack::pack::Pack for PackMock {
+    type File = FileMock;
+
+    fn get_file_by_path(
+        &self,
+        path: &str,
+    ) -> Option<&Self::File> {
+        match path {
+            "/present" => Some(&FILE_MAX_CACHE),
+            "/café file.txt" => Some(&FILE_NO_CACHE),
+            _ => None,
+        }
+    }
+}
+
+async fn call(request: TestRequest) -> ServiceResponse {
+    call_with_options(request, ResponderOptions::default()).await
+}
+
+async fn call_with_options(
+    request: TestRequest,
+    responder_options: ResponderOptions,
+) -> ServiceResponse {
+    let responder =
+        ResponderOwned::new_with_options(Arc::new(PackMock), Arc::new(responder_options));
+
+    let service = init_service(
+        App::new()
+            .service(web_static_pack::actix::scope("/static", responder.clone()))
+            .default_service(web_static_pack::actix::default_service(responder)),
+    )
+    .await;
+
+    call_service(&service, request.to_request()).await
+}
+
+fn header_as_string(
+    response: &ServiceResponse,
+    name: header::HeaderName,
+) -> String {
+    response
+        .headers()
+        .get(&name)
+        .unwrap_or_else(|| panic!("missing header {name}"))
+        .to_str()
+        .unwrap()
+        .to_owned()
+}
+
+fn body(response: ServiceResponse) -> Vec<u8> {
+    response
+        .into_body()
+        .try_into_bytes()
+        .map_err(|_| ())
+        .unwrap()
+        .to_vec()
+}
+
+#[actix_web::test]
+async fn responds_to_typical_request() {
+    let response = call(
+        TestRequest::get()
+            .uri("/present")
+            .insert_header((header::ACCEPT_ENCODING, "br, gzip")),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        header_as_string(&response, header::CONTENT_TYPE),
+        "text/plain; charset=utf-8"
+    );
+    assert_eq!(
+        header_as_string(&response, header::ETAG), // line break
+        "\"etagvalue\""
+    );
+    assert_eq!(
+        header_as_string(&response, header::CACHE_CONTROL),
+        "max-age=31536000, immutable"
+    );
+    assert_eq!(header_as_string(&response, header::CONTENT_ENCODING), "br");
+    assert_eq!(body(response), b"content-brotli");
+}
+
+#[test_case("br", b"content-brotli"; "brotli")]
+#[test_case("gzip", b"content-gzip-longer-than-brotli"; "gzip")]
+#[test_case("", b"content-identity-is-the-longest-and-least-preferred-option"; "identity")]
+#[actix_web::test]
+async fn responds_with_other_encodings(
+    accept_encoding: &str,
+    expected: &[u8],
+) {
+    let response = call(
+        TestRequest::get()
+            .uri("/present")
+            .insert_header((header::ACCEPT_ENCODING, accept_encoding)),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body(response), expected);
+}
+
+#[actix_web::test]
+async fn responds_inside_scope() {
+    let response = call(TestRequest::get().uri("/static/present")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = call(TestRequest::get().uri("/static/missing")).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[test_case("/caf%C3%A9%20file.txt"; "root")]
+#[test_case("/static/caf%C3%A9%20file.txt"; "scope")]
+#[actix_web::test]
+async fn responds_to_percent_encoded_path(uri: &str) {
+    let response = call(TestRequest::get().uri(uri)).await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn decodes_percent_encoded_path_once() {
+    let response = call(TestRequest::get().uri("/caf%25C3%25A9%2520file.txt")).await;
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[test_case("/caf%C3%A9%20file.txt?v=hash", "max-age=31536000, immutable"; "versioned")]
+#[test_case("/static/caf%C3%A9%20file.txt?v=hash", "max-age=31536000, immutable"; "versioned in scope")]
+#[test_case("/caf%C3%A9%20file.txt?v=", "no-cache"; "empty version")]
+#[test_case("/caf%C3%A9%20file.txt", "no-cache"; "not versioned")]
+#[actix_web::test]
+async fn applies_query_cache_buster(
+    uri: &str,
+    expected: &str,
+) {
+    let response = call_with_options(
+        TestRequest::get().uri(uri),
+        ResponderOptions {
+            query_policy: QueryPolicy::CacheBuster {
+                parameter_name: "v".to_owned(),
+            },
+            ..ResponderOptions::default()
+        },
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(header_as_string(&response, header::CACHE_CONTROL), expected);
+}
+
+#[actix_web::test]
+async fn resolves_no_body_for_head_request() {
+    let response = call(TestRequest::default().method(Method::HEAD).uri("/present")).await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        header_as_string(&response, header::ETAG), // line break
+        "\"etagvalue\""
+    );
+    assert_eq!(body(response), b"");
+}
+
+#[actix_web::test]
+async fn resolves_not_modified_for_matching_etag() {
+    let response = call(
+        TestRequest::get()
+            .uri("/present")
+            .insert_header((header::IF_NONE_MATCH, "\"etagvalue\"")),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(
+        header_as_string(&response, header::ETAG), // line break
+        "\"etagvalue\""
+    );
+    assert!(response.headers().get(header::CONTENT_TYPE).is_none());
+    assert_eq!(body(response), b"");
+}
+
+#[actix_web::test]
+async fn resolves_error_for_invalid_method() {
+    let response = call(TestRequest::post().uri("/present")).await;
+
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(body(response), b"");
+}
+
+#[actix_web::test]
+async fn resolves_error_for_file_not_found() {
+    let response = call(TestRequest::get().uri("/missing")).await;
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(body(response), b"");
+}